@@ -15,7 +15,11 @@ const __dirname = path.dirname(fileURLToPath(import.meta.url));
 
 const PROGRAM_ID = new PublicKey("9gjTj718N5cbUkUXV6vYmovEeh6hcDm9HAGeXFMJmcjY");
 const CONFIG_SEED = "ghost-config";
-const CONFIG_SPACE = 32 + 1 + 1 + 4 + (10 * 32);
+// Matches ProgramConfig::space: a fixed header region, then the validator
+// count and one 32-byte slot per validator
+const CONFIG_HEADER_LEN = 1024;
+const MAX_VALIDATORS = 10;
+const CONFIG_SPACE = CONFIG_HEADER_LEN + 4 + (MAX_VALIDATORS * 32);
 
 function serializeInitialize(admin, validatorThreshold, maxValidators) {
   const buf = Buffer.alloc(32 + 1 + 1);
@@ -88,7 +92,7 @@ async function main() {
     
     const initPayload = Buffer.concat([
       Buffer.from([0]),
-      serializeInitialize(payer.publicKey.toBytes(), 1, 10),
+      serializeInitialize(payer.publicKey.toBytes(), 1, MAX_VALIDATORS),
     ]);
 
     const initIx = new TransactionInstruction({
@@ -12,7 +12,11 @@ import * as path from "path";
 
 const PROGRAM_ID = new PublicKey("9gjTj718N5cbUkUXV6vYmovEeh6hcDm9HAGeXFMJmcjY");
 const CONFIG_SEED = "ghost-config";
-const CONFIG_SPACE = 32 + 1 + 1 + 4 + (10 * 32); // admin + threshold + max + vec header + 10 validators
+// Matches ProgramConfig::space: a fixed header region, then the validator
+// count and one 32-byte slot per validator
+const CONFIG_HEADER_LEN = 1024;
+const MAX_VALIDATORS = 10;
+const CONFIG_SPACE = CONFIG_HEADER_LEN + 4 + (MAX_VALIDATORS * 32);
 
 // Manual serialization for Borsh compatibility
 function serializeInitialize(admin: Uint8Array, validatorThreshold: number, maxValidators: number): Buffer {
@@ -105,7 +109,7 @@ async function main() {
     
     const initPayload = Buffer.concat([
       Buffer.from([0]), // Instruction index for Initialize
-      serializeInitialize(payer.publicKey.toBytes(), 1, 10),
+      serializeInitialize(payer.publicKey.toBytes(), 1, MAX_VALIDATORS),
     ]);
 
     const configAccountFinal = await PublicKey.createWithSeed(
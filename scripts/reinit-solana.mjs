@@ -33,7 +33,11 @@ console.log("Admin:", keypair.publicKey.toBase58());
 
 // Create new config account with different seed
 const CONFIG_SEED = "ghost-config-v2";
-const CONFIG_SPACE = 32 + 1 + 1 + 4 + (10 * 32); // admin + threshold + max + vec header + 10 validators
+// Matches ProgramConfig::space: a fixed header region, then the validator
+// count and one 32-byte slot per validator
+const CONFIG_HEADER_LEN = 1024;
+const MAX_VALIDATORS = 10;
+const CONFIG_SPACE = CONFIG_HEADER_LEN + 4 + (MAX_VALIDATORS * 32);
 
 const configAccount = await PublicKey.createWithSeed(
   keypair.publicKey,
@@ -85,7 +89,7 @@ initData.writeUInt8(1, offset);
 offset += 1;
 
 // max_validators
-initData.writeUInt8(10, offset);
+initData.writeUInt8(MAX_VALIDATORS, offset);
 
 console.log("\nInitializing config...");
 console.log("Init data:", initData.toString("hex"));
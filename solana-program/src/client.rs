@@ -0,0 +1,106 @@
+//! Off-chain helpers: deriving instruction inputs and auditing event logs.
+//!
+//! Everything here is pure and also compiled into the program, so the values
+//! a client derives are exactly the ones the program checks.
+
+use borsh::BorshSerialize;
+use solana_program::{keccak, pubkey::Pubkey};
+
+use crate::events::{state_hash, GhostEvent, PoolSnapshot};
+use crate::{ChainFamily, GhostAccount, LiquidityPool};
+
+/// Deterministic ghost ID: keccak256 over the initiator, destination chain
+/// (u64 LE), destination address, amount (u64 LE) and a caller-chosen nonce
+/// (u64 LE). CreateGhost checks it when `verify_id` is set.
+pub fn derive_ghost_id(
+    initiator: &Pubkey,
+    destination_chain: u64,
+    destination_address: &[u8; 64],
+    amount: u64,
+    nonce: u64,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        initiator.as_ref(),
+        &destination_chain.to_le_bytes(),
+        destination_address,
+        &amount.to_le_bytes(),
+        &nonce.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Encodes a native address for a `destination_address` on a chain of
+/// `family`: 20 bytes for EVM, 32 for Solana, up to 64 for other chains,
+/// stored left-aligned. None if the address has the wrong length or is empty.
+pub fn encode_destination(family: ChainFamily, address: &[u8]) -> Option<[u8; 64]> {
+    let valid = match family {
+        ChainFamily::Evm => address.len() == 20,
+        ChainFamily::Solana => address.len() == 32,
+        ChainFamily::Other => address.len() <= 64,
+    };
+    if !valid || address.iter().all(|b| *b == 0) {
+        return None;
+    }
+    let mut encoded = [0u8; 64];
+    encoded[..address.len()].copy_from_slice(address);
+    Some(encoded)
+}
+
+/// Why an event log doesn't reproduce an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// Event `index` doesn't follow from the state before it: an event is
+    /// missing, duplicated or out of order
+    Gap { index: usize },
+    /// The log replays cleanly but ends in a different state than the account
+    Mismatch,
+}
+
+/// Rebuilds a ghost from its GhostEvents, oldest first, starting from an
+/// account that held nothing. None means the ghost ends closed.
+pub fn replay_ghost(events: &[GhostEvent]) -> Result<Option<GhostAccount>, ReplayError> {
+    replay(events.iter().map(|event| (event.previous, event.ghost.as_ref())))
+}
+
+/// Replays `events` and checks the result against the stored ghost (None for
+/// a closed or never-created account)
+pub fn verify_ghost(events: &[GhostEvent], on_chain: Option<&GhostAccount>) -> Result<(), ReplayError> {
+    let replayed = replay_ghost(events)?;
+    matches(replayed.as_ref(), on_chain)
+}
+
+/// Rebuilds a pool from its PoolSnapshots, oldest first, starting from
+/// InitializePool
+pub fn replay_pool(events: &[PoolSnapshot]) -> Result<Option<LiquidityPool>, ReplayError> {
+    replay(events.iter().map(|event| (event.previous, Some(&event.pool))))
+}
+
+/// Replays `events` and checks the result against the stored pool
+pub fn verify_pool(events: &[PoolSnapshot], on_chain: &LiquidityPool) -> Result<(), ReplayError> {
+    let replayed = replay_pool(events)?;
+    matches(replayed.as_ref(), Some(on_chain))
+}
+
+/// Applies each (previous hash, new state) step in order, requiring every step
+/// to name the state the one before it left behind
+fn replay<'a, T, I>(steps: I) -> Result<Option<T>, ReplayError>
+where
+    T: BorshSerialize + Clone + 'a,
+    I: Iterator<Item = ([u8; 32], Option<&'a T>)>,
+{
+    let mut current: Option<T> = None;
+    for (index, (previous, next)) in steps.enumerate() {
+        if previous != state_hash(current.as_ref()) {
+            return Err(ReplayError::Gap { index });
+        }
+        current = next.cloned();
+    }
+    Ok(current)
+}
+
+fn matches<T: BorshSerialize>(replayed: Option<&T>, on_chain: Option<&T>) -> Result<(), ReplayError> {
+    if state_hash(replayed) != state_hash(on_chain) {
+        return Err(ReplayError::Mismatch);
+    }
+    Ok(())
+}
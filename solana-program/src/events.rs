@@ -0,0 +1,119 @@
+//! Structured events emitted through `sol_log_data`.
+//!
+//! Each event is logged as two data fields: a short tag naming the event,
+//! then the borsh-encoded body. Indexers match on the tag and decode the body
+//! with the struct defined here.
+//!
+//! Every write to a ghost or pool also logs its full new state together with
+//! `state_hash` of the state it replaced, so the log for one account forms a
+//! chain that `client::replay_ghost`/`client::replay_pool` can check for gaps.
+
+use alloc::vec::Vec;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{keccak, log::sol_log_data};
+
+use crate::{GhostAccount, LiquidityPool};
+
+/// Fixed-point scale of `PoolState::price_per_share` (lamports per share)
+pub const PRICE_SCALE: u128 = 1_000_000_000;
+
+/// Pool totals after a deposit, withdrawal or payout
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PoolState {
+    pub seed: [u8; 32],
+    pub total_deposited: u64,
+    pub total_shares: u64,
+    pub price_per_share: u64, // Lamports per share, scaled by PRICE_SCALE
+}
+
+impl PoolState {
+    pub const TAG: &'static [u8] = b"pool_state";
+
+    pub fn from_pool(pool: &LiquidityPool) -> Self {
+        // An empty pool mints shares 1:1 with lamports
+        let price = if pool.total_shares == 0 {
+            PRICE_SCALE
+        } else {
+            pool.total_deposited as u128 * PRICE_SCALE / pool.total_shares as u128
+        };
+        PoolState {
+            seed: pool.seed,
+            total_deposited: pool.total_deposited,
+            total_shares: pool.total_shares,
+            price_per_share: u64::try_from(price).unwrap_or(u64::MAX),
+        }
+    }
+
+    pub fn emit(&self) {
+        emit(Self::TAG, self);
+    }
+}
+
+/// A ghost account after a write; `ghost` is None once the account is closed
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct GhostEvent {
+    pub previous: [u8; 32], // state_hash of the ghost before the write
+    pub ghost: Option<GhostAccount>,
+}
+
+impl GhostEvent {
+    pub const TAG: &'static [u8] = b"ghost";
+
+    pub fn emit(&self) {
+        emit(Self::TAG, self);
+    }
+}
+
+/// A pool's full state after a write
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct PoolSnapshot {
+    pub previous: [u8; 32], // state_hash of the pool before the write
+    pub pool: LiquidityPool,
+}
+
+impl PoolSnapshot {
+    pub const TAG: &'static [u8] = b"pool_snapshot";
+
+    pub fn emit(&self) {
+        emit(Self::TAG, self);
+    }
+}
+
+/// Any event this program logs
+#[derive(Clone)]
+pub enum Event {
+    PoolState(PoolState),
+    Ghost(GhostEvent),
+    PoolSnapshot(PoolSnapshot),
+}
+
+impl Event {
+    /// Decodes one logged event from its tag and body, or None for a tag
+    /// this version doesn't know or a body that doesn't parse
+    pub fn decode(tag: &[u8], body: &[u8]) -> Option<Self> {
+        match tag {
+            PoolState::TAG => PoolState::try_from_slice(body).ok().map(Event::PoolState),
+            GhostEvent::TAG => GhostEvent::try_from_slice(body).ok().map(Event::Ghost),
+            PoolSnapshot::TAG => PoolSnapshot::try_from_slice(body).ok().map(Event::PoolSnapshot),
+            _ => None,
+        }
+    }
+}
+
+/// keccak256 of a state's borsh encoding; all zeros for an account that
+/// holds no state (never written, or closed)
+pub fn state_hash<T: BorshSerialize>(state: Option<&T>) -> [u8; 32] {
+    match state.and_then(|state| state.try_to_vec().ok()) {
+        Some(bytes) => keccak::hash(&bytes).to_bytes(),
+        None => [0u8; 32],
+    }
+}
+
+fn emit<T: BorshSerialize>(tag: &[u8], event: &T) {
+    let body: Vec<u8> = match event.try_to_vec() {
+        Ok(body) => body,
+        Err(_) => return,
+    };
+    sol_log_data(&[tag, &body]);
+}
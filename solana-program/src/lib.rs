@@ -4,27 +4,46 @@ use alloc::vec::Vec;
 use alloc::vec;
 use alloc::format;
 
-use borsh::{BorshDeserialize, BorshSerialize};
+pub mod client;
+pub mod events;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+
+use borsh::{maybestd::io, BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     clock::Clock,
-    entrypoint,
+    ed25519_program,
     entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvar::Sysvar,
+    system_program,
+    sysvar::{self, rent::Rent, Sysvar},
 };
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+/// First byte of versioned instruction data. Unversioned (legacy) data
+/// starts with a variant index instead, which never reaches this value.
+pub const INSTRUCTION_PREFIX: u8 = 0xFF;
+
+/// Encoding version following `INSTRUCTION_PREFIX`. Older encodings stay
+/// decodable here while clients upgrade:
+/// - unprefixed: the launch layouts
+/// - 1: before SetPoolLimits.min_withdrawal, DepositToPool.min_shares_out,
+///   SetVaaEmitter.source_chain and the MirrorGhost nonce/recipient/timestamp
+pub const INSTRUCTION_VERSION: u8 = 2;
 
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = GhostInstruction::try_from_slice(instruction_data)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let instruction = GhostInstruction::unpack(instruction_data)?;
     Processor::process(program_id, accounts, instruction)
 }
 
@@ -46,6 +65,10 @@ pub enum GhostInstruction {
         destination_address: [u8; 64],
         source_token: Pubkey,
         destination_token: Pubkey,
+        min_out: u64,
+        /// Require ghost_id == client::derive_ghost_id(initiator, ..., nonce)
+        verify_id: bool,
+        nonce: u64,
     },
     LockGhost {
         ghost_id: [u8; 32],
@@ -61,6 +84,13 @@ pub enum GhostInstruction {
         burn_proof: [u8; 32],
         source_token: Pubkey,
         destination_token: Pubkey,
+        min_out: u64,
+        /// Must exceed the last nonce mirrored from `source_chain`
+        source_nonce: u64,
+        /// Solana account the burn pays out to; MintGhost must name it
+        recipient: Pubkey,
+        /// Time of the burn on the source chain, which finality counts from
+        source_ts: i64,
     },
     MintGhost {
         ghost_id: [u8; 32],
@@ -69,6 +99,7 @@ pub enum GhostInstruction {
     },
     AcknowledgeRemote {
         ghost_id: [u8; 32],
+        source_chain: u64,
     },
     DestroyGhost {
         ghost_id: [u8; 32],
@@ -77,14 +108,17 @@ pub enum GhostInstruction {
     // LIQUIDITY POOL INSTRUCTIONS
     // ═══════════════════════════════════════════════════════════════════════
     
-    /// Initialize a new liquidity pool
+    /// Initialize a new liquidity pool backing `dest_token` payouts
     InitializePool {
         pool_seed: [u8; 32],
+        dest_token: Pubkey,
     },
     
-    /// Deposit SOL into the pool (LP gets shares)
+    /// Deposit SOL into the pool (LP gets shares), failing if it would mint
+    /// fewer than `min_shares_out`
     DepositToPool {
         amount: u64,
+        min_shares_out: u64,
     },
     
     /// Withdraw SOL from pool (burn shares)
@@ -100,7 +134,7 @@ pub enum GhostInstruction {
         amount: u64,
     },
     
-    /// Record incoming payment intent (from EVM)
+    /// Record incoming payment intent (from EVM) at its intent PDA
     RecordPaymentIntent {
         intent_id: [u8; 32],
         sender_chain: u64,
@@ -108,19 +142,631 @@ pub enum GhostInstruction {
         amount: u64,
         dest_token: Pubkey,
     },
+
+    /// Cap CreateGhost amounts to a fraction (bps) of the referenced pool's
+    /// available liquidity (admin only, 0 = full liquidity)
+    SetLiquidityCap {
+        liquidity_cap_bps: u16,
+    },
+
+    /// Refund a burned ghost whose remote mint failed (validator threshold)
+    /// Releases any escrowed lamports back to the initiator
+    RefundGhost {
+        ghost_id: [u8; 32],
+        failure_proof: [u8; 32],
+    },
+
+    /// Mirror a remote burn from a posted Wormhole VAA
+    MirrorGhostFromVaa {
+        vaa_account: Pubkey,
+    },
+
+    /// Configure the Wormhole core program and the trusted emitter (admin only)
+    /// `source_chain` is the bridge's id for the emitter's Wormhole chain
+    SetVaaEmitter {
+        wormhole_program: Pubkey,
+        emitter_chain: u16,
+        emitter_address: [u8; 32],
+        source_chain: u64,
+    },
+
+    /// Raise the validator cap, reallocating the config account (admin only)
+    /// Extra rent is paid by the admin
+    GrowConfig {
+        new_max_validators: u8,
+    },
+
+    /// Limit each relayer's payout volume per time window (admin only)
+    /// A zero cap disables rate limiting
+    SetRateLimit {
+        window_secs: i64,
+        max_volume: u64,
+    },
+
+    /// Correct the destination of a ghost that is still Created (initiator only).
+    /// The address is checked against the destination chain's address family
+    /// as at creation; `destination_token` names a token on that chain, which
+    /// this program keeps no whitelist of, so it is recorded unchecked here
+    /// and at creation alike and the destination bridge validates it on mint.
+    UpdateDestination {
+        ghost_id: [u8; 32],
+        destination_address: [u8; 64],
+        destination_token: Pubkey,
+    },
+
+    /// Rewrite a pre-versioning account in the current layout (admin only).
+    /// Legacy accounts already load, and writes upgrade those with room to
+    /// spare; this grows the rest. New fields take their defaults; the admin
+    /// pays any extra rent.
+    MigrateAccount {
+        kind: AccountKind,
+    },
+
+    /// Hard floor on distinct validator signers for burns and mints,
+    /// applied on top of validator_threshold (admin only)
+    SetMinSigners {
+        min_signers: u8,
+    },
+
+    /// Mark an in-flight ghost as Failed (validator threshold)
+    /// Escrow on local ghosts goes back to the initiator
+    FailGhost {
+        ghost_id: [u8; 32],
+    },
+
+    /// Close a terminal (Settled, Refunded or Failed) ghost and reclaim its rent
+    /// (remote ghosts need the validator threshold)
+    CloseGhost {
+        ghost_id: [u8; 32],
+    },
+
+    /// Fail if the pool's accounting invariants do not hold (read-only,
+    /// meant to be run via simulation by monitoring)
+    CheckInvariants {
+        pool_seed: [u8; 32],
+    },
+
+    /// Set the account that receives settled escrow (admin only)
+    SetTreasury {
+        treasury: Pubkey,
+    },
+
+    /// Walk a prematurely locked ghost back to Created before any burn
+    /// (validator threshold)
+    UnlockGhost {
+        ghost_id: [u8; 32],
+    },
+
+    /// Halt ghost creation, mirroring, minting and payouts (admin or guardian)
+    SetPaused {
+        paused: bool,
+    },
+
+    /// Protocol fee taken from each payout, in bps (admin only)
+    SetFee {
+        fee_bps: u16,
+    },
+
+    /// Write a versioned ConfigSummary through return data (read-only)
+    GetConfig,
+
+    /// Set the guardian key, whose only power is SetPaused (admin only)
+    SetGuardian {
+        guardian: Pubkey,
+    },
+
+    /// Shrink an over-allocated config or pool to its used length and send
+    /// the freed rent to the treasury (admin only). A config's other surplus
+    /// goes too; a pool's stays with its LPs.
+    SweepExcessRent {
+        kind: AccountKind,
+    },
+
+    /// Minimum seconds between LockGhost and BurnGhost (admin only, 0 = none)
+    SetBurnDelay {
+        burn_delay: i64,
+    },
+
+    /// Stop or resume deposits and payouts on a pool (admin only)
+    SetPoolActive {
+        active: bool,
+    },
+
+    /// Send a wound-down pool's remaining lamports above rent to
+    /// `destination` and mark it closed (admin only, no LP shares outstanding;
+    /// the locked MINIMUM_LIQUIDITY shares belong to no LP)
+    DrainPool {
+        destination: Pubkey,
+    },
+
+    /// Deposit and withdrawal guardrails for a pool (admin only, 0 = unbounded)
+    SetPoolLimits {
+        min_deposit: u64,
+        max_pool_size: u64,
+        min_withdrawal: u64,
+    },
+
+    /// Burn and mint a Locked ghost in one step with a single validator.
+    /// Only executable in builds with the `devnet` feature; always decodes so
+    /// instruction indices match across builds. Local ghosts mint on their
+    /// destination chain, so this now always fails with InvalidState.
+    DevFastMint {
+        ghost_id: [u8; 32],
+        burn_proof: [u8; 32],
+        mint_proof: [u8; 32],
+        recipient: Pubkey,
+    },
+
+    /// Chain ID this deployment runs on (admin only, 0 = SOLANA_CHAIN_ID)
+    SetLocalChainId {
+        chain_id: u64,
+    },
+
+    /// Seconds an LP must wait after depositing before withdrawing from a
+    /// pool (admin only, 0 = none)
+    SetWithdrawGracePeriod {
+        grace_period: i64,
+    },
+
+    /// Enable or disable a remote chain in the chain registry (admin only)
+    SetChain {
+        chain_id: u64,
+        enabled: bool,
+    },
+
+    /// Shrink an executed or cancelled payment intent to a ClosedIntent
+    /// record, returning the rest of its rent to the relayer that recorded
+    /// it. Intents have no expiry, so cancellation is the only release.
+    CloseIntent {
+        intent_id: [u8; 32],
+    },
+
+    /// Seconds a burn mirrored from `chain_id` must age before it can be
+    /// minted (admin only, 0 = none)
+    SetChainFinality {
+        chain_id: u64,
+        finality_delay: i64,
+    },
+
+    /// Fold a second LP position of the signer in the same pool into the
+    /// first and close it, returning its rent to the owner
+    MergePositions,
+
+    /// Bond every relayer must post before recording or paying intents, and
+    /// the wait between unbonding and releasing it (admin only, 0 = no bond)
+    SetRelayerBond {
+        min_bond: u64,
+        cooldown: i64,
+    },
+
+    /// Add lamports to the signing relayer's bond, reactivating it
+    PostBond {
+        amount: u64,
+    },
+
+    /// Stop the signing relayer's bond from backing payouts and start the
+    /// release cooldown
+    UnbondRelayer,
+
+    /// Return an unbonded relayer's bond and rent once the cooldown has passed
+    ReleaseBond,
+
+    /// Confiscate up to `amount` of a relayer's bond into the treasury
+    /// (admin only)
+    SlashRelayer {
+        relayer: Pubkey,
+        amount: u64,
+    },
+
+    /// Cap a recorded intent's amount to a fraction (bps) of the backing
+    /// pool's total deposits (admin only, 0 = total deposits)
+    SetIntentCap {
+        intent_cap_bps: u16,
+    },
+
+    /// Most intents that may be recorded but not yet executed or cancelled
+    /// at once (admin only, 0 = unbounded)
+    SetMaxOpenIntents {
+        max_open_intents: u32,
+    },
+
+    /// Override the payout fee for intents from `chain_id` (admin only,
+    /// None = use the global fee)
+    SetChainFee {
+        chain_id: u64,
+        fee_bps: Option<u16>,
+    },
+
+    /// Apply validator changes, then the threshold and fee if given, as one
+    /// update that is validated before anything is written (admin only)
+    BatchConfigure {
+        validators: Vec<(Pubkey, bool)>,
+        threshold: Option<u8>,
+        fee_bps: Option<u16>,
+    },
+
+    /// Cap a pool's payouts per fixed `window_secs` window at `max_volume`,
+    /// tripping its circuit breaker once the cap is reached (admin only,
+    /// 0 = disabled)
+    SetBreaker {
+        window_secs: i64,
+        max_volume: u64,
+    },
+
+    /// Clear a tripped circuit breaker and start a fresh window (admin only)
+    ResetBreaker,
+
+    /// Lamports of a pool's liquidity kept back from intent payouts so LPs
+    /// can always withdraw them (admin only, 0 = none)
+    SetReservedBuffer {
+        reserved_buffer: u64,
+    },
+
+    /// Queue a forced move of a wedged ghost to a terminal state (admin
+    /// only); executable after FORCE_SETTLE_TIMELOCK
+    ProposeForceSettle {
+        ghost_id: [u8; 32],
+        target_state: GhostState,
+    },
+
+    /// Execute a force settle whose timelock has passed (admin only)
+    ForceSettle {
+        ghost_id: [u8; 32],
+    },
+
+    /// Drop a pending force settle (admin only)
+    CancelForceSettle {
+        ghost_id: [u8; 32],
+    },
+
+    /// Record that the signing validator is online
+    Heartbeat,
+
+    /// Allow or disallow ExecutePayment payouts into accounts owned by
+    /// `program` (admin only)
+    SetRecipientProgram {
+        program: Pubkey,
+        allowed: bool,
+    },
+
+    /// Record a token's decimals and display symbol (admin only; symbol at
+    /// most TOKEN_SYMBOL_LEN bytes)
+    SetTokenMetadata {
+        mint: Pubkey,
+        decimals: u8,
+        symbol: String,
+    },
+
+    /// Retract an intent that hasn't been fully paid (validator only)
+    CancelIntent {
+        intent_id: [u8; 32],
+    },
+
+    /// Replace the lone admin with `threshold`-of-`admins` (admin only; an
+    /// empty set with threshold 0 hands control back to `admin`)
+    SetAdmins {
+        admins: Vec<Pubkey>,
+        threshold: u8,
+    },
+
+    /// DestroyGhost followed by CloseGhost in one instruction, sending the
+    /// ghost's rent to `rent_destination`
+    SettleAndClose {
+        ghost_id: [u8; 32],
+        rent_destination: Pubkey,
+    },
+
+    /// Address format of `chain_id`, which destinations on it must match
+    /// (admin only)
+    SetChainAddressFamily {
+        chain_id: u64,
+        family: ChainFamily,
+    },
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+// Indexes of the variants whose layout changed since an older encoding
+const CREATE_GHOST: u8 = 2;
+const MIRROR_GHOST: u8 = 5;
+const ACKNOWLEDGE_REMOTE: u8 = 7;
+const INITIALIZE_POOL: u8 = 9;
+const DEPOSIT_TO_POOL: u8 = 10;
+const SET_VAA_EMITTER: u8 = 17;
+const SET_POOL_LIMITS: u8 = 36;
+
+/// CreateGhost before the slippage floor and ghost ID check
+#[derive(BorshDeserialize)]
+struct LegacyCreateGhost {
+    ghost_id: [u8; 32],
+    amount: u64,
+    destination_chain: u64,
+    destination_address: [u8; 64],
+    source_token: Pubkey,
+    destination_token: Pubkey,
+}
+
+/// SetPoolLimits before the withdrawal minimum
+#[derive(BorshDeserialize)]
+struct V1SetPoolLimits {
+    min_deposit: u64,
+    max_pool_size: u64,
+}
+
+impl GhostInstruction {
+    /// Encodes the instruction as the current version
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_PREFIX, INSTRUCTION_VERSION];
+        self.serialize(&mut data).expect("writing to a Vec can't fail");
+        data
+    }
+
+    /// Decodes `INSTRUCTION_PREFIX` and a version followed by the borsh
+    /// instruction, or unprefixed legacy data. Fields an older encoding lacks
+    /// take the value that keeps its old behavior; variants whose missing
+    /// fields have no such value are refused with a message naming them.
+    /// Anything that doesn't decode is `GhostError::InvalidInstruction`;
+    /// handlers report what's wrong with an instruction that did.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        match data.split_first() {
+            Some((&INSTRUCTION_PREFIX, rest)) => {
+                let (&version, body) = rest.split_first().ok_or(GhostError::InvalidInstruction)?;
+                match version {
+                    INSTRUCTION_VERSION => Self::decode(body),
+                    1 => Self::unpack_v1(body),
+                    _ => {
+                        msg!("Unsupported instruction version {}", version);
+                        Err(GhostError::InvalidInstruction.into())
+                    }
+                }
+            }
+            Some(_) => Self::unpack_legacy(data),
+            None => Err(GhostError::InvalidInstruction.into()),
+        }
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(data).map_err(|_| GhostError::InvalidInstruction.into())
+    }
+
+    fn decode_body<T: BorshDeserialize>(body: &[u8]) -> Result<T, ProgramError> {
+        T::try_from_slice(body).map_err(|_| GhostError::InvalidInstruction.into())
+    }
+
+    fn unpack_v1(data: &[u8]) -> Result<Self, ProgramError> {
+        let (&variant, body) = data.split_first().ok_or(GhostError::InvalidInstruction)?;
+        match variant {
+            MIRROR_GHOST => Self::outdated("MirrorGhost must carry a source nonce, recipient and source time"),
+            DEPOSIT_TO_POOL => Ok(GhostInstruction::DepositToPool {
+                amount: Self::decode_body(body)?,
+                min_shares_out: 0,
+            }),
+            SET_VAA_EMITTER => Self::outdated("SetVaaEmitter must name the emitter's bridge chain id"),
+            SET_POOL_LIMITS => {
+                let limits: V1SetPoolLimits = Self::decode_body(body)?;
+                Ok(GhostInstruction::SetPoolLimits {
+                    min_deposit: limits.min_deposit,
+                    max_pool_size: limits.max_pool_size,
+                    min_withdrawal: 0,
+                })
+            }
+            _ => Self::decode(data),
+        }
+    }
+
+    fn unpack_legacy(data: &[u8]) -> Result<Self, ProgramError> {
+        let (&variant, body) = data.split_first().ok_or(GhostError::InvalidInstruction)?;
+        match variant {
+            CREATE_GHOST => {
+                let ghost: LegacyCreateGhost = Self::decode_body(body)?;
+                Ok(GhostInstruction::CreateGhost {
+                    ghost_id: ghost.ghost_id,
+                    amount: ghost.amount,
+                    destination_chain: ghost.destination_chain,
+                    destination_address: ghost.destination_address,
+                    source_token: ghost.source_token,
+                    destination_token: ghost.destination_token,
+                    min_out: 0,
+                    verify_id: false,
+                    nonce: 0,
+                })
+            }
+            ACKNOWLEDGE_REMOTE => Self::outdated("AcknowledgeRemote must name the source chain"),
+            INITIALIZE_POOL => Self::outdated("InitializePool must name the pool's token and registry"),
+            // Layouts unchanged since launch, bar the ones version 1 also lacks
+            _ => Self::unpack_v1(data),
+        }
+    }
+
+    fn outdated(what: &str) -> Result<Self, ProgramError> {
+        msg!("Outdated instruction encoding: {}", what);
+        Err(GhostError::InvalidInstruction.into())
+    }
+}
+
+/// Seconds between proposing and executing a ForceSettle
+pub const FORCE_SETTLE_TIMELOCK: i64 = 7 * 24 * 60 * 60;
+
+/// Most keys an admin set may hold; bounded so the set fits the config header
+pub const MAX_ADMINS: usize = 8;
+
+/// Tag every versioned program account starts with. Accounts written before
+/// versioning are bare borsh and begin with arbitrary field bytes (a ghost id,
+/// an admin key) that a lone version byte could collide with; a seven-byte
+/// tag makes a legacy account passing for a versioned one implausible.
+pub const ACCOUNT_TAG: [u8; 7] = *b"GHOSTWL";
+
+/// The tag followed by the layout version byte
+pub const ACCOUNT_HEADER_LEN: usize = ACCOUNT_TAG.len() + 1;
+
+/// Layout version of versioned borsh accounts
+pub const ACCOUNT_VERSION: u8 = 1;
+
+/// Config layout version: a fixed header region followed by validator slots
+pub const CONFIG_VERSION: u8 = 2;
+
+/// Bytes reserved for the config account header and borsh header. Keeping the
+/// validator region at a fixed offset lets SetValidator rewrite a single slot.
+pub const CONFIG_HEADER_LEN: usize = 1024;
+
+/// Layout version of a versioned account, or None for a blank account or
+/// one still in the layout it had before versioning
+pub fn account_version(data: &[u8]) -> Option<u8> {
+    let header = data.get(..ACCOUNT_HEADER_LEN)?;
+    (header[..ACCOUNT_TAG.len()] == ACCOUNT_TAG).then_some(header[ACCOUNT_TAG.len()])
+}
+
+/// Stamps the account tag and `version` onto the start of `data`
+fn write_account_header(data: &mut [u8], version: u8) -> ProgramResult {
+    let header = data
+        .get_mut(..ACCOUNT_HEADER_LEN)
+        .ok_or(GhostError::AccountSerialization)?;
+    header[..ACCOUNT_TAG.len()].copy_from_slice(&ACCOUNT_TAG);
+    header[ACCOUNT_TAG.len()] = version;
+    Ok(())
+}
+
+/// Account types that predate the account header and may still hold bare
+/// borsh. Only these fall back to their legacy layout when loaded, so a
+/// legacy account can't be passed off as some newer type.
+pub trait LegacyLayout: BorshDeserialize {}
+
+impl LegacyLayout for GhostAccount {}
+impl LegacyLayout for LiquidityPool {}
+impl LegacyLayout for LPPosition {}
+impl LegacyLayout for PaymentIntent {}
+impl LegacyLayout for RelayerStats {}
+
+/// Account types MigrateAccount knows how to upgrade
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AccountKind {
+    Config,
+    Ghost,
+    Pool,
+    Position,
+    Intent,
+    RelayerStats,
+}
+
+/// Chain ID the bridge uses for Solana (matches SOLANA_CHAIN_ID in config/)
+pub const SOLANA_CHAIN_ID: u64 = 1_399_811_149;
+
+/// Denominator for all basis-point settings
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Shares a pool's first deposit mints to no one. They keep total_shares from
+/// ever being tiny, so lamports donated to the pool can't inflate one share's
+/// price far enough to round the next depositor's shares down to nothing.
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+#[derive(BorshSerialize, Debug, Clone, Default)]
 pub struct ProgramConfig {
     pub admin: Pubkey,
     pub validator_threshold: u8,
     pub max_validators: u8,
     pub validators: Vec<Pubkey>,
+    pub liquidity_cap_bps: u16,
+    pub wormhole_program: Pubkey,
+    pub vaa_emitter_chain: u16,
+    pub vaa_emitter_address: [u8; 32],
+    pub next_vaa_sequence: u64,    // Unused: consumed VAAs are claimed per sequence
+    pub relayer_window_secs: i64,
+    pub relayer_window_cap: u64,
+    pub total_ghosts_created: u64,
+    pub min_signers: u8,
+    pub treasury: Pubkey,
+    pub paused: bool,
+    pub fee_bps: u16,
+    pub guardian: Pubkey,
+    pub burn_delay: i64,
+    pub local_chain_id: u64,
+    pub relayer_min_bond: u64,
+    pub bond_cooldown: i64,
+    pub intent_cap_bps: u16,
+    pub ghost_counts: GhostCounts,
+    pub admins: Vec<Pubkey>,       // Empty = `admin` alone governs
+    pub admin_threshold: u8,       // Admin signatures required when `admins` is set
+    pub max_open_intents: u32,     // Cap on recorded, unfinished intents (0 = none)
+    pub open_intents: u32,         // Intents recorded but not yet executed or cancelled
+    pub vaa_source_chain: u64,     // Bridge chain id of the trusted VAA emitter's chain
 }
 
 impl ProgramConfig {
+    /// Header region, validator count, then one 32-byte slot per validator
     pub fn space(max_validators: usize) -> usize {
-        32 + 1 + 1 + 4 + max_validators * 32
+        CONFIG_HEADER_LEN + 4 + max_validators * 32
+    }
+
+    /// Largest ghost amount a pool holding `available_liquidity` will back
+    pub fn liquidity_cap(&self, available_liquidity: u64) -> u64 {
+        let bps = match self.liquidity_cap_bps as u64 {
+            0 => BPS_DENOMINATOR,
+            bps => bps.min(BPS_DENOMINATOR),
+        };
+        (available_liquidity as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    /// Largest intent a pool holding `total_deposited` will accept
+    pub fn intent_cap(&self, total_deposited: u64) -> u64 {
+        let bps = match self.intent_cap_bps as u64 {
+            0 => BPS_DENOMINATOR,
+            bps => bps.min(BPS_DENOMINATOR),
+        };
+        (total_deposited as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    /// Distinct validator signatures a multi-signer step needs: the threshold,
+    /// but never fewer than the `min_signers` floor
+    pub fn required_signers(&self) -> usize {
+        self.validator_threshold.max(self.min_signers) as usize
+    }
+
+    /// Invariants every saved config must satisfy: the validator set fits
+    /// `max_validators`, and so do the signer requirements
+    pub fn check_bounds(&self) -> Result<(), GhostError> {
+        if self.validators.len() > self.max_validators as usize {
+            return Err(GhostError::ValidatorLimit);
+        }
+        if self.validator_threshold > self.max_validators || self.min_signers > self.max_validators {
+            return Err(GhostError::InvalidConfig);
+        }
+        let threshold = self.admin_threshold as usize;
+        let threshold_ok = match self.admins.len() {
+            0 => threshold == 0,
+            len => (1..=len).contains(&threshold),
+        };
+        if self.admins.len() > MAX_ADMINS || !threshold_ok {
+            return Err(GhostError::InvalidConfig);
+        }
+        Ok(())
+    }
+
+    /// Chain ID of this deployment
+    pub fn local_chain(&self) -> u64 {
+        match self.local_chain_id {
+            0 => SOLANA_CHAIN_ID,
+            id => id,
+        }
+    }
+
+    /// Protocol fee on a payout of `amount`, rounded down
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        fee_at(self.fee_bps, amount)
+    }
+
+    /// Fee on a payout of `amount` for an intent from `chain`: the chain's
+    /// override when it has one, otherwise the global fee
+    pub fn route_fee(&self, chain: Option<&ChainConfig>, amount: u64) -> u64 {
+        match chain.and_then(|chain| chain.fee_bps) {
+            Some(fee_bps) => fee_at(fee_bps, amount),
+            None => self.fee_for(amount),
+        }
+    }
+
+    pub fn ensure_not_paused(&self) -> Result<(), GhostError> {
+        if self.paused {
+            Err(GhostError::Paused)
+        } else {
+            Ok(())
+        }
     }
 
     pub fn is_validator(&self, key: &Pubkey) -> bool {
@@ -136,17 +782,183 @@ impl ProgramConfig {
     }
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+/// `fee_bps` of `amount`, rounded down
+fn fee_at(fee_bps: u16, amount: u64) -> u64 {
+    let bps = (fee_bps as u64).min(BPS_DENOMINATOR);
+    (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Layout version of ConfigSummary; bumped only when its fields change
+pub const CONFIG_SUMMARY_VERSION: u8 = 1;
+
+/// Stable view of the config returned by GetConfig, independent of the
+/// account layout. `paused` and `fee_bps` are the SetPaused and SetFee settings.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
+pub struct ConfigSummary {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub validator_threshold: u8,
+    pub max_validators: u8,
+    pub validator_count: u32,
+    pub paused: bool,
+    pub fee_bps: u16,
+}
+
+impl From<&ProgramConfig> for ConfigSummary {
+    fn from(config: &ProgramConfig) -> Self {
+        ConfigSummary {
+            version: CONFIG_SUMMARY_VERSION,
+            admin: config.admin,
+            validator_threshold: config.validator_threshold,
+            max_validators: config.max_validators,
+            validator_count: config.validators.len() as u32,
+            paused: config.paused,
+            fee_bps: config.fee_bps,
+        }
+    }
+}
+
+/// Up to `len` validators starting at `start`, clamped to the set, so clients
+/// can page through large validator sets
+pub fn validators_page(config: &ProgramConfig, start: usize, len: usize) -> &[Pubkey] {
+    let start = start.min(config.validators.len());
+    let end = start.saturating_add(len).min(config.validators.len());
+    &config.validators[start..end]
+}
+
+/// Reads the validator slots of a config account's data without decoding
+/// the header
+pub fn config_validators(data: &[u8]) -> Result<Vec<Pubkey>, ProgramError> {
+    let count = data
+        .get(CONFIG_HEADER_LEN..CONFIG_HEADER_LEN + 4)
+        .ok_or(GhostError::AccountDeserialization)?;
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize;
+    let start = CONFIG_HEADER_LEN + 4;
+    let slots = data
+        .get(start..start + count * 32)
+        .ok_or(GhostError::AccountDeserialization)?;
+    slots
+        .chunks_exact(32)
+        .map(|slot| Pubkey::try_from(slot).map_err(|_| GhostError::AccountDeserialization.into()))
+        .collect()
+}
+
+impl BorshDeserialize for ProgramConfig {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let admin = Pubkey::deserialize_reader(reader)?;
+        let validator_threshold = u8::deserialize_reader(reader)?;
+        let max_validators = u8::deserialize_reader(reader)?;
+        let validators = Vec::<Pubkey>::deserialize_reader(reader)?;
+
+        // Settings appended after launch default to zero on older configs
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail)?;
+        let mut tail: &[u8] = &tail;
+
+        Ok(ProgramConfig {
+            admin,
+            validator_threshold,
+            max_validators,
+            validators,
+            liquidity_cap_bps: read_or_default(&mut tail)?,
+            wormhole_program: read_or_default(&mut tail)?,
+            vaa_emitter_chain: read_or_default(&mut tail)?,
+            vaa_emitter_address: read_or_default(&mut tail)?,
+            next_vaa_sequence: read_or_default(&mut tail)?,
+            relayer_window_secs: read_or_default(&mut tail)?,
+            relayer_window_cap: read_or_default(&mut tail)?,
+            total_ghosts_created: read_or_default(&mut tail)?,
+            min_signers: read_or_default(&mut tail)?,
+            treasury: read_or_default(&mut tail)?,
+            paused: read_or_default(&mut tail)?,
+            fee_bps: read_or_default(&mut tail)?,
+            guardian: read_or_default(&mut tail)?,
+            burn_delay: read_or_default(&mut tail)?,
+            local_chain_id: read_or_default(&mut tail)?,
+            relayer_min_bond: read_or_default(&mut tail)?,
+            bond_cooldown: read_or_default(&mut tail)?,
+            intent_cap_bps: read_or_default(&mut tail)?,
+            ghost_counts: read_or_default(&mut tail)?,
+            admins: read_or_default(&mut tail)?,
+            admin_threshold: read_or_default(&mut tail)?,
+            max_open_intents: read_or_default(&mut tail)?,
+            open_intents: read_or_default(&mut tail)?,
+            vaa_source_chain: read_or_default(&mut tail)?,
+        })
+    }
+}
+
+/// Number of ghosts currently in each tracked state, kept in the config so
+/// monitoring can read protocol-wide totals without scanning ghost accounts.
+/// Ghosts that predate the counters are not included.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct GhostCounts {
+    pub created: u64,
+    pub locked: u64,
+    pub burned: u64,
+    pub minted: u64,
+    pub settled: u64,
+}
+
+impl GhostCounts {
+    /// Ghosts currently in `state`, or None for states that aren't counted
+    pub fn get(&self, state: GhostState) -> Option<u64> {
+        let mut counts = *self;
+        counts.slot_mut(state).map(|count| *count)
+    }
+
+    /// Moves one ghost from `from` to `to`
+    pub fn record_transition(&mut self, from: GhostState, to: GhostState) {
+        if from == to {
+            return;
+        }
+        if let Some(count) = self.slot_mut(from) {
+            *count = count.saturating_sub(1);
+        }
+        if let Some(count) = self.slot_mut(to) {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    fn slot_mut(&mut self, state: GhostState) -> Option<&mut u64> {
+        match state {
+            GhostState::Created => Some(&mut self.created),
+            GhostState::Locked => Some(&mut self.locked),
+            GhostState::Burned => Some(&mut self.burned),
+            GhostState::Minted => Some(&mut self.minted),
+            GhostState::Settled => Some(&mut self.settled),
+            GhostState::None | GhostState::Refunded | GhostState::Failed => None,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub enum GhostState {
+    #[default]
     None,
     Created,
     Locked,
     Burned,
     Minted,
     Settled,
+    Refunded,
+    Failed,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Clone)]
+impl GhostState {
+    /// A transfer can fail any time before value lands on the destination:
+    /// Created, Locked, or Burned but not yet minted
+    pub fn can_fail(&self) -> bool {
+        matches!(self, GhostState::Created | GhostState::Locked | GhostState::Burned)
+    }
+
+    /// No further transitions; the account may be closed
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, GhostState::Settled | GhostState::Refunded | GhostState::Failed)
+    }
+}
+
+#[derive(BorshSerialize, Clone)]
 pub struct GhostAccount {
     pub ghost_id: [u8; 32],
     pub initiator: Pubkey,
@@ -163,32 +975,345 @@ pub struct GhostAccount {
     pub mint_proof: [u8; 32],
     pub is_remote: bool,
     pub remote_ack: bool,
+    pub validator_set_hash: [u8; 32],
+    pub min_out: u64,
+    pub source_ts: i64,
+    pub escrow_vault: bool,
+    pub fee: Option<u64>,          // Fixed at creation or mirroring (None = predates fixed fees)
+    pub token_escrow: bool,        // Source tokens held in a token account of the vault
+    pub required_signers: u8,      // Signatures its mint needs, fixed at burn (0 = unrecorded)
 }
 
 impl GhostAccount {
     pub fn space() -> usize {
-        32 + 32 + 32 + 32 + 8 + 64 + 1 + 8 + 8 + 8 + 8 + 32 + 32 + 1 + 1
+        ACCOUNT_HEADER_LEN + 32 + 32 + 32 + 32 + 8 + 64 + 1 + 8 + 8 + 8 + 8 + 32 + 32 + 1 + 1 + 32 + 8 + 8 + 1 + 9 + 1 + 1
     }
-}
 
-// ═══════════════════════════════════════════════════════════════════════════════
-// LIQUIDITY POOL STRUCTURES
-// ═══════════════════════════════════════════════════════════════════════════════
+    /// Signatures needed to finish this ghost. Threshold changes are not
+    /// retroactive: a burned ghost keeps the requirement recorded when it was
+    /// burned or mirrored, so raising the threshold can't strand it and
+    /// lowering it doesn't weaken it. Ghosts burned before the requirement was
+    /// recorded follow the live config.
+    pub fn required_signers(&self, config: &ProgramConfig) -> usize {
+        match self.required_signers {
+            0 => config.required_signers(),
+            required => required as usize,
+        }
+    }
 
-/// Liquidity pool state - holds SOL for instant cross-chain payments
-#[derive(BorshSerialize, BorshDeserialize, Clone)]
-pub struct LiquidityPool {
+    /// Fee this ghost is charged: the one fixed when it was created or
+    /// mirrored, or the current fee for ghosts that predate fixed fees
+    pub fn settlement_fee(&self, config: &ProgramConfig) -> u64 {
+        self.fee.unwrap_or_else(|| config.fee_for(self.amount))
+    }
+
+    /// What the recipient is owed once the fee is taken out, and what
+    /// `min_out` is checked against. The program itself moves no fee: a
+    /// settled local ghost's whole escrow goes to the treasury, and the fee is
+    /// the part of it not delivered on the destination chain.
+    pub fn delivered_amount(&self, config: &ProgramConfig) -> u64 {
+        self.amount.saturating_sub(self.settlement_fee(config))
+    }
+}
+
+impl Default for GhostAccount {
+    fn default() -> Self {
+        GhostAccount {
+            ghost_id: [0u8; 32],
+            initiator: Pubkey::default(),
+            source_token: Pubkey::default(),
+            destination_token: Pubkey::default(),
+            destination_chain: 0,
+            destination_address: [0u8; 64],
+            state: GhostState::None,
+            amount: 0,
+            lock_ts: 0,
+            burn_ts: 0,
+            mint_ts: 0,
+            burn_proof: [0u8; 32],
+            mint_proof: [0u8; 32],
+            is_remote: false,
+            remote_ack: false,
+            validator_set_hash: [0u8; 32],
+            min_out: 0,
+            source_ts: 0,
+            escrow_vault: false,
+            fee: None,
+            token_escrow: false,
+            required_signers: 0,
+        }
+    }
+}
+
+impl BorshDeserialize for GhostAccount {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let ghost_id = <[u8; 32]>::deserialize_reader(reader)?;
+        let initiator = Pubkey::deserialize_reader(reader)?;
+        let source_token = Pubkey::deserialize_reader(reader)?;
+        let destination_token = Pubkey::deserialize_reader(reader)?;
+        let destination_chain = u64::deserialize_reader(reader)?;
+        let destination_address = <[u8; 64]>::deserialize_reader(reader)?;
+        let state = GhostState::deserialize_reader(reader)?;
+        let amount = u64::deserialize_reader(reader)?;
+        let lock_ts = i64::deserialize_reader(reader)?;
+        let burn_ts = i64::deserialize_reader(reader)?;
+        let mint_ts = i64::deserialize_reader(reader)?;
+        let burn_proof = <[u8; 32]>::deserialize_reader(reader)?;
+        let mint_proof = <[u8; 32]>::deserialize_reader(reader)?;
+        let is_remote = bool::deserialize_reader(reader)?;
+        let remote_ack = bool::deserialize_reader(reader)?;
+
+        // Fields appended since launch default to zero on older ghosts
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail)?;
+        let mut tail: &[u8] = &tail;
+
+        Ok(GhostAccount {
+            ghost_id,
+            initiator,
+            source_token,
+            destination_token,
+            destination_chain,
+            destination_address,
+            state,
+            amount,
+            lock_ts,
+            burn_ts,
+            mint_ts,
+            burn_proof,
+            mint_proof,
+            is_remote,
+            remote_ack,
+            validator_set_hash: read_or_default(&mut tail)?,
+            min_out: read_or_default(&mut tail)?,
+            source_ts: read_or_default(&mut tail)?,
+            escrow_vault: read_or_default(&mut tail)?,
+            fee: read_or_default(&mut tail)?,
+            token_escrow: read_or_default(&mut tail)?,
+            required_signers: read_or_default(&mut tail)?,
+        })
+    }
+}
+
+/// Validator set frozen at burn time - PDA at [b"validators", set_hash]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ValidatorSnapshot {
+    pub set_hash: [u8; 32],        // validator_set_hash of this set
+    pub required_signers: u8,      // Signatures needed from the set
+    pub validators: Vec<Pubkey>,   // Members at snapshot time
+}
+
+impl ValidatorSnapshot {
+    pub fn space(validators: usize) -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 1 + 4 + validators * 32
+    }
+}
+
+/// Identifies a validator set together with the signatures it requires
+pub fn validator_set_hash(validators: &[Pubkey], required_signers: u8) -> [u8; 32] {
+    let required = [required_signers];
+    let mut parts: Vec<&[u8]> = validators.iter().map(|v| v.as_ref()).collect();
+    parts.push(&required);
+    solana_program::hash::hashv(&parts).to_bytes()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// LIQUIDITY POOL STRUCTURES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Liquidity pool state - holds SOL for instant cross-chain payments
+#[derive(BorshSerialize, Clone, Default)]
+pub struct LiquidityPool {
     pub seed: [u8; 32],           // Pool identifier
     pub total_deposited: u64,      // Total SOL in pool
     pub total_shares: u64,         // Total LP shares issued
-    pub total_fees: u64,           // Accumulated fees
+    pub total_fees: u64,           // Fees kept before fees were credited to LPs
     pub available_liquidity: u64,  // Currently available
     pub active: bool,              // Pool accepting deposits
+    pub lifetime_volume: u128,     // Total lamports paid out (metrics only)
+    pub payment_count: u64,        // Payments executed (metrics only)
+    pub closed: bool,              // Drained for migration; never reactivates
+    pub min_deposit: u64,          // Smallest accepted deposit (0 = any)
+    pub max_pool_size: u64,        // Cap on total_deposited (0 = unbounded)
+    pub withdraw_grace_period: i64, // Seconds after a deposit before withdrawing
+    pub min_withdrawal: u64,       // Smallest partial withdrawal in lamports (0 = any)
+    pub breaker_window_secs: i64,  // Circuit breaker window length
+    pub breaker_max_volume: u64,   // Most payout volume per window (0 = off)
+    pub breaker_window_start: i64, // Start of the current breaker window
+    pub breaker_volume: u64,       // Lamports paid out in the current window
+    pub breaker_tripped: bool,     // Payouts halted until ResetBreaker
+    pub reserved_buffer: u64,      // Liquidity payouts may not touch; LPs still can
 }
 
 impl LiquidityPool {
+    /// Account size for a new pool, which always uses the zero-copy layout
     pub fn space() -> usize {
-        32 + 8 + 8 + 8 + 8 + 1
+        POOL_DATA_OFFSET + core::mem::size_of::<PoolData>()
+    }
+
+    /// Shares minted to the depositor for a deposit of `amount`. The first
+    /// deposit into an empty pool is priced 1:1 less the `locked_shares`
+    /// minted alongside it.
+    ///
+    /// Rounding policy: both directions round against the caller. Deposits
+    /// round shares down here and withdrawals round lamports down in
+    /// `shares_value`, so any dust stays in `total_deposited` and every
+    /// outstanding share is always backed by at least its implied value.
+    /// Returns None on overflow or when the deposit is too small for a share.
+    pub fn shares_for_deposit(&self, amount: u64) -> Option<u64> {
+        let shares = if self.total_shares == 0 || self.total_deposited == 0 {
+            amount.checked_sub(self.locked_shares())?
+        } else {
+            let shares = amount as u128 * self.total_shares as u128 / self.total_deposited as u128;
+            u64::try_from(shares).ok()?
+        };
+        self.total_shares.checked_add(shares)?.checked_add(self.locked_shares())?;
+        (shares > 0).then_some(shares)
+    }
+
+    /// Shares the next deposit mints to no one: MINIMUM_LIQUIDITY for the
+    /// first deposit into an empty pool, otherwise none
+    pub fn locked_shares(&self) -> u64 {
+        if self.total_shares == 0 {
+            MINIMUM_LIQUIDITY
+        } else {
+            0
+        }
+    }
+
+    /// Lamports redeemable for `shares`, rounded down (0 for an empty pool);
+    /// see `shares_for_deposit` for the rounding policy
+    pub fn shares_value(&self, shares: u64) -> u64 {
+        if self.total_shares == 0 {
+            return 0;
+        }
+        let value = shares as u128 * self.total_deposited as u128 / self.total_shares as u128;
+        u64::try_from(value).unwrap_or(u64::MAX)
+    }
+
+    /// Folds lamports sent straight to the pool account, beyond what its
+    /// bookkeeping and rent account for, into the deposits, so the surplus
+    /// raises the value of existing shares instead of skewing the price a new
+    /// depositor pays. Returns the amount absorbed.
+    pub fn absorb_surplus(&mut self, lamports: u64, rent_exempt: u64) -> u64 {
+        let tracked = (self.available_liquidity as u128)
+            + (self.total_fees as u128)
+            + (rent_exempt as u128);
+        let surplus = u64::try_from((lamports as u128).saturating_sub(tracked)).unwrap_or(u64::MAX);
+        let Some(total_deposited) = self.total_deposited.checked_add(surplus) else {
+            return 0;
+        };
+        let Some(available_liquidity) = self.available_liquidity.checked_add(surplus) else {
+            return 0;
+        };
+        self.total_deposited = total_deposited;
+        self.available_liquidity = available_liquidity;
+        surplus
+    }
+
+    /// Credits a payout fee kept by the pool to its LPs: it joins the
+    /// deposits shares are priced from and the liquidity behind them. Fees
+    /// held in `total_fees` by earlier versions, which no one could claim,
+    /// are credited along with it.
+    pub fn credit_fees(&mut self, fee: u64) -> Option<()> {
+        let credited = fee.checked_add(self.total_fees)?;
+        self.total_deposited = self.total_deposited.checked_add(credited)?;
+        self.available_liquidity = self.available_liquidity.checked_add(credited)?;
+        self.total_fees = 0;
+        Some(())
+    }
+
+    /// Liquidity intent payouts may draw on: what's available above the
+    /// reserved buffer
+    pub fn payable_liquidity(&self) -> u64 {
+        self.available_liquidity.saturating_sub(self.reserved_buffer)
+    }
+
+    /// Charges a payout of `amount` against the circuit breaker window at
+    /// `now`. Payouts are refused once they would take the window past
+    /// `breaker_max_volume`, and the payout that uses up the window exactly
+    /// trips the breaker, halting every payout after it until ResetBreaker.
+    ///
+    /// The window is fixed rather than rolling: it starts with the first
+    /// payout after the previous one ended and resets once
+    /// `breaker_window_secs` have passed, so up to twice the limit can go out
+    /// across a window boundary.
+    pub fn record_breaker_payout(&mut self, now: i64, amount: u64) -> Result<(), GhostError> {
+        if self.breaker_tripped {
+            return Err(GhostError::BreakerTripped);
+        }
+        if self.breaker_max_volume == 0 {
+            return Ok(());
+        }
+        if now - self.breaker_window_start >= self.breaker_window_secs {
+            self.breaker_window_start = now;
+            self.breaker_volume = 0;
+        }
+        let volume = self.breaker_volume.saturating_add(amount);
+        if volume > self.breaker_max_volume {
+            return Err(GhostError::BreakerTripped);
+        }
+        self.breaker_volume = volume;
+        if volume == self.breaker_max_volume {
+            self.breaker_tripped = true;
+        }
+        Ok(())
+    }
+
+    /// Checks the accounting invariants against the account's lamport balance
+    /// and rent-exempt minimum, reporting the first one that is broken
+    pub fn check_invariants(&self, lamports: u64, rent_exempt: u64) -> Result<(), GhostError> {
+        if self.available_liquidity > self.total_deposited {
+            return Err(GhostError::LiquidityExceedsDeposits);
+        }
+        let backing = (self.available_liquidity as u128)
+            + (self.total_fees as u128)
+            + (rent_exempt as u128);
+        if (lamports as u128) < backing {
+            return Err(GhostError::PoolUnderfunded);
+        }
+        if (self.total_shares > 0) != (self.total_deposited > 0) {
+            return Err(GhostError::SharesDepositMismatch);
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for LiquidityPool {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let seed = <[u8; 32]>::deserialize_reader(reader)?;
+        let total_deposited = u64::deserialize_reader(reader)?;
+        let total_shares = u64::deserialize_reader(reader)?;
+        let total_fees = u64::deserialize_reader(reader)?;
+        let available_liquidity = u64::deserialize_reader(reader)?;
+        let active = bool::deserialize_reader(reader)?;
+
+        // Metrics were appended after launch; older pools have no bytes for them
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail)?;
+        let mut tail: &[u8] = &tail;
+
+        Ok(LiquidityPool {
+            seed,
+            total_deposited,
+            total_shares,
+            total_fees,
+            available_liquidity,
+            active,
+            lifetime_volume: read_or_default(&mut tail)?,
+            payment_count: read_or_default(&mut tail)?,
+            closed: read_or_default(&mut tail)?,
+            min_deposit: read_or_default(&mut tail)?,
+            max_pool_size: read_or_default(&mut tail)?,
+            withdraw_grace_period: read_or_default(&mut tail)?,
+            min_withdrawal: read_or_default(&mut tail)?,
+            breaker_window_secs: read_or_default(&mut tail)?,
+            breaker_max_volume: read_or_default(&mut tail)?,
+            breaker_window_start: read_or_default(&mut tail)?,
+            breaker_volume: read_or_default(&mut tail)?,
+            breaker_tripped: read_or_default(&mut tail)?,
+            reserved_buffer: read_or_default(&mut tail)?,
+        })
     }
 }
 
@@ -203,12 +1328,119 @@ pub struct LPPosition {
 
 impl LPPosition {
     pub fn space() -> usize {
-        32 + 32 + 8 + 8
+        ACCOUNT_HEADER_LEN + 32 + 32 + 8 + 8
     }
 }
 
-/// Payment intent received from another chain
-#[derive(BorshSerialize, BorshDeserialize, Clone)]
+/// Zero-copy pool layout version; borsh pools stay at ACCOUNT_VERSION
+pub const POOL_ZERO_COPY_VERSION: u8 = 2;
+
+/// PoolData starts right after the account header, keeping it 8-byte aligned
+pub const POOL_DATA_OFFSET: usize = ACCOUNT_HEADER_LEN;
+
+/// In-place layout of a zero-copy pool account, mapped with bytemuck.
+/// Admin setters change fields in place through `update_pool`. Deposits,
+/// withdrawals and payments copy it into a LiquidityPool, whose share and
+/// breaker math clients share, and copy it back: no borsh, but one copy.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct PoolData {
+    pub seed: [u8; 32],
+    pub total_deposited: u64,
+    pub total_shares: u64,
+    pub total_fees: u64,
+    pub available_liquidity: u64,
+    pub lifetime_volume: [u8; 16], // u128 LE, stored as bytes to keep 8-byte alignment
+    pub payment_count: u64,
+    pub active: u8,
+    pub closed: u8,
+    pub _padding: [u8; 6],
+    pub min_deposit: u64,
+    pub max_pool_size: u64,
+    pub withdraw_grace_period: i64,
+    pub min_withdrawal: u64,
+    pub breaker_window_secs: i64,
+    pub breaker_max_volume: u64,
+    pub breaker_window_start: i64,
+    pub breaker_volume: u64,
+    pub breaker_tripped: u8,
+    pub _padding2: [u8; 7],
+    pub reserved_buffer: u64,
+    pub _reserved: [u8; 48],       // Room for fields appended later
+}
+
+impl From<&LiquidityPool> for PoolData {
+    fn from(pool: &LiquidityPool) -> Self {
+        PoolData {
+            seed: pool.seed,
+            total_deposited: pool.total_deposited,
+            total_shares: pool.total_shares,
+            total_fees: pool.total_fees,
+            available_liquidity: pool.available_liquidity,
+            lifetime_volume: pool.lifetime_volume.to_le_bytes(),
+            payment_count: pool.payment_count,
+            closed: pool.closed as u8,
+            min_deposit: pool.min_deposit,
+            max_pool_size: pool.max_pool_size,
+            withdraw_grace_period: pool.withdraw_grace_period,
+            min_withdrawal: pool.min_withdrawal,
+            breaker_window_secs: pool.breaker_window_secs,
+            breaker_max_volume: pool.breaker_max_volume,
+            breaker_window_start: pool.breaker_window_start,
+            breaker_volume: pool.breaker_volume,
+            breaker_tripped: pool.breaker_tripped as u8,
+            reserved_buffer: pool.reserved_buffer,
+            active: pool.active as u8,
+            ..PoolData::zeroed()
+        }
+    }
+}
+
+impl From<&PoolData> for LiquidityPool {
+    fn from(data: &PoolData) -> Self {
+        LiquidityPool {
+            seed: data.seed,
+            total_deposited: data.total_deposited,
+            total_shares: data.total_shares,
+            total_fees: data.total_fees,
+            available_liquidity: data.available_liquidity,
+            active: data.active != 0,
+            lifetime_volume: u128::from_le_bytes(data.lifetime_volume),
+            payment_count: data.payment_count,
+            closed: data.closed != 0,
+            min_deposit: data.min_deposit,
+            max_pool_size: data.max_pool_size,
+            withdraw_grace_period: data.withdraw_grace_period,
+            min_withdrawal: data.min_withdrawal,
+            breaker_window_secs: data.breaker_window_secs,
+            breaker_max_volume: data.breaker_max_volume,
+            breaker_window_start: data.breaker_window_start,
+            breaker_volume: data.breaker_volume,
+            breaker_tripped: data.breaker_tripped != 0,
+            reserved_buffer: data.reserved_buffer,
+        }
+    }
+}
+
+/// Maps a zero-copy pool account's data in place
+pub fn pool_data_mut(data: &mut [u8]) -> Result<&mut PoolData, ProgramError> {
+    if account_version(data) != Some(POOL_ZERO_COPY_VERSION) {
+        return Err(GhostError::AccountVersionMismatch.into());
+    }
+    let bytes = data
+        .get_mut(POOL_DATA_OFFSET..POOL_DATA_OFFSET + core::mem::size_of::<PoolData>())
+        .ok_or(GhostError::AccountDeserialization)?;
+    bytemuck::try_from_bytes_mut(bytes).map_err(|_| GhostError::AccountDeserialization.into())
+}
+
+/// Current lamport value of an LP position, using the same math as
+/// WithdrawFromPool so clients and the program always agree
+pub fn position_value(pool: &LiquidityPool, position: &LPPosition) -> u64 {
+    pool.shares_value(position.shares)
+}
+
+/// Payment intent received from another chain - PDA at [b"intent", intent_id]
+#[derive(BorshSerialize, Clone)]
 pub struct PaymentIntent {
     pub intent_id: [u8; 32],       // Unique ID
     pub sender_chain: u64,         // Source chain ID
@@ -216,40 +1448,548 @@ pub struct PaymentIntent {
     pub amount: u64,               // Amount to deliver
     pub dest_token: Pubkey,        // Token to send
     pub recipient: Pubkey,         // Recipient on Solana
-    pub executed: bool,            // Has been paid out
+    pub executed: bool,            // Fully paid out
     pub timestamp: i64,            // When received
+    pub amount_paid: u64,          // Delivered so far across partial payouts
+    pub relayer: Pubkey,           // Recorded the intent and funded its rent
+    pub cancelled: bool,           // Retracted before it was fully paid
+    pub counted: bool,             // Included in the config's open_intents
 }
 
 impl PaymentIntent {
     pub fn space() -> usize {
-        32 + 8 + 64 + 8 + 32 + 32 + 1 + 8
+        ACCOUNT_HEADER_LEN + 32 + 8 + 64 + 8 + 32 + 32 + 1 + 8 + 8 + 32 + 1 + 1
     }
 }
 
-pub struct Processor;
+impl BorshDeserialize for PaymentIntent {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let intent_id = <[u8; 32]>::deserialize_reader(reader)?;
+        let sender_chain = u64::deserialize_reader(reader)?;
+        let sender_address = <[u8; 64]>::deserialize_reader(reader)?;
+        let amount = u64::deserialize_reader(reader)?;
+        let dest_token = Pubkey::deserialize_reader(reader)?;
+        let recipient = Pubkey::deserialize_reader(reader)?;
+        let executed = bool::deserialize_reader(reader)?;
+        let timestamp = i64::deserialize_reader(reader)?;
 
-impl Processor {
-    pub fn process(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        instruction: GhostInstruction,
-    ) -> ProgramResult {
-        match instruction {
-            GhostInstruction::Initialize {
-                admin,
-                validator_threshold,
-                max_validators,
-            } => Self::initialize(program_id, accounts, admin, validator_threshold, max_validators),
-            GhostInstruction::SetValidator { validator, enabled } => {
-                Self::set_validator(program_id, accounts, validator, enabled)
+        // Fields appended since launch default to zero on older intents
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail)?;
+        let mut tail: &[u8] = &tail;
+
+        Ok(PaymentIntent {
+            intent_id,
+            sender_chain,
+            sender_address,
+            amount,
+            dest_token,
+            recipient,
+            executed,
+            timestamp,
+            amount_paid: read_or_default(&mut tail)?,
+            relayer: read_or_default(&mut tail)?,
+            cancelled: read_or_default(&mut tail)?,
+            counted: read_or_default(&mut tail)?,
+        })
+    }
+}
+
+/// What CloseIntent leaves at an intent's address once its rent is
+/// reclaimed, so the same intent can never be recorded and paid again
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ClosedIntent {
+    pub intent_id: [u8; 32],       // Intent that was closed
+    pub closed_at: i64,            // When its rent was reclaimed
+}
+
+impl ClosedIntent {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 8
+    }
+}
+
+/// Queued ForceSettle - PDA at [b"force_settle", ghost_id]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct PendingForceSettle {
+    pub ghost_id: [u8; 32],        // Ghost to settle
+    pub target_state: GhostState,  // Terminal state it will be moved to
+    pub proposed_at: i64,          // Start of the timelock
+    pub proposer: Pubkey,          // Admin that queued it
+}
+
+impl PendingForceSettle {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 1 + 8 + 32
+    }
+}
+
+/// Consumed VAA - PDA at [b"vaa", emitter_chain, emitter_address, sequence]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct VaaClaim {
+    pub sequence: u64,             // Emitter sequence the VAA carried
+    pub ghost_id: [u8; 32],        // Ghost it was mirrored into
+}
+
+impl VaaClaim {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 8 + 32
+    }
+}
+
+/// Token -> pool mapping - PDA at [b"pool_registry", dest_token]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct PoolRegistry {
+    pub dest_token: Pubkey,        // Token this pool pays out
+    pub pool_seed: [u8; 32],       // Seed of the backing pool
+    pub pool: Pubkey,              // Backing pool account
+}
+
+impl PoolRegistry {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 32 + 32
+    }
+}
+
+/// Chain registry entry - PDA at [b"chain", chain_id (u64 LE)]
+#[derive(BorshSerialize, Clone, Default)]
+pub struct ChainConfig {
+    pub chain_id: u64,             // Remote chain this entry describes
+    pub enabled: bool,             // Accepting acknowledgments from this chain
+    pub finality_delay: i64,       // Seconds after a source burn before minting
+    pub fee_bps: Option<u16>,      // Payout fee override for intents from this chain
+    pub last_nonce: u64,           // Highest source nonce mirrored from this chain
+    pub address_family: Option<ChainFamily>, // Destination format; unset until the admin names it
+}
+
+impl ChainConfig {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 8 + 1 + 8 + 3 + 8 + 2
+    }
+}
+
+impl BorshDeserialize for ChainConfig {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let chain_id = u64::deserialize_reader(reader)?;
+        let enabled = bool::deserialize_reader(reader)?;
+
+        // Settings appended later default to zero on older entries
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail)?;
+        let mut tail: &[u8] = &tail;
+
+        Ok(ChainConfig {
+            chain_id,
+            enabled,
+            finality_delay: read_or_default(&mut tail)?,
+            fee_bps: read_or_default(&mut tail)?,
+            last_nonce: read_or_default(&mut tail)?,
+            address_family: read_or_default(&mut tail)?,
+        })
+    }
+}
+
+/// Validator liveness - PDA at [b"heartbeat", validator]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct ValidatorHeartbeat {
+    pub validator: Pubkey,         // Validator this record tracks
+    pub last_seen_ts: i64,         // Time of its latest Heartbeat
+}
+
+impl ValidatorHeartbeat {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 8
+    }
+
+    /// Heard from within `window` seconds of `now`
+    pub fn is_live(&self, now: i64, window: i64) -> bool {
+        now.saturating_sub(self.last_seen_ts) <= window
+    }
+}
+
+/// Members of `validators` with a heartbeat within `window` seconds of `now`,
+/// for checking the threshold is reachable before relying on it
+pub fn live_validators(
+    validators: &[Pubkey],
+    heartbeats: &[ValidatorHeartbeat],
+    now: i64,
+    window: i64,
+) -> usize {
+    validators
+        .iter()
+        .filter(|v| heartbeats.iter().any(|h| h.validator == **v && h.is_live(now, window)))
+        .count()
+}
+
+/// Payout whitelist entry - PDA at [b"recipient_program", program]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct RecipientProgram {
+    pub program: Pubkey,           // Owner program of accepted recipient accounts
+    pub allowed: bool,             // Payouts currently permitted
+}
+
+impl RecipientProgram {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 1
+    }
+}
+
+/// Longest symbol TokenMetadata stores
+pub const TOKEN_SYMBOL_LEN: usize = 10;
+
+/// Display and conversion info for a token - PDA at [b"token_metadata", mint]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct TokenMetadata {
+    pub mint: Pubkey,                     // Token this entry describes
+    pub decimals: u8,                     // Base-unit decimals for amount conversion
+    pub symbol: [u8; TOKEN_SYMBOL_LEN],   // UTF-8 symbol, zero-padded
+}
+
+impl TokenMetadata {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 1 + TOKEN_SYMBOL_LEN
+    }
+
+    /// The symbol without its padding
+    pub fn symbol(&self) -> &str {
+        let len = self.symbol.iter().position(|b| *b == 0).unwrap_or(TOKEN_SYMBOL_LEN);
+        core::str::from_utf8(&self.symbol[..len]).unwrap_or("")
+    }
+}
+
+/// Relayer payout accounting - PDA at [b"relayer", relayer]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct RelayerStats {
+    pub relayer: Pubkey,           // Relayer this record throttles
+    pub window_start: i64,         // Start of the current window
+    pub last_payment_ts: i64,      // Most recent payout
+    pub payments_in_window: u64,   // Payouts in the current window
+    pub volume_in_window: u64,     // Lamports paid in the current window
+}
+
+impl RelayerStats {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 8 + 8 + 8 + 8
+    }
+}
+
+/// Lamports a relayer has staked on its conduct - PDA at [b"bond", relayer]
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct RelayerBond {
+    pub relayer: Pubkey,           // Relayer that posted the bond
+    pub amount: u64,               // Bonded lamports, excluding rent
+    pub active: bool,              // Backing the relayer's payouts
+    pub unbonded_at: i64,          // When the relayer unbonded (0 while active)
+}
+
+impl RelayerBond {
+    pub fn space() -> usize {
+        ACCOUNT_HEADER_LEN + 32 + 8 + 1 + 8
+    }
+}
+
+/// Reads an appended field, falling back to its default when the account
+/// predates the field and the bytes are simply not there.
+fn read_or_default<T: BorshDeserialize + Default>(buf: &mut &[u8]) -> io::Result<T> {
+    if buf.is_empty() {
+        Ok(T::default())
+    } else {
+        T::deserialize(buf)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// CHAIN ADDRESSES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Address format of a destination chain, recorded in its registry entry
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChainFamily {
+    /// 20-byte account, stored left-aligned in `destination_address`
+    Evm,
+    /// 32-byte pubkey, stored left-aligned in `destination_address`
+    Solana,
+    /// No dedicated encoding; any non-empty address is passed through
+    Other,
+}
+
+impl ChainFamily {
+    /// Canonical form of `address` for this family, or None if it's malformed.
+    /// EVM addresses may also arrive left-padded to 32 bytes.
+    pub fn normalize_address(self, address: &[u8; 64]) -> Option<[u8; 64]> {
+        if address.iter().all(|b| *b == 0) {
+            return None;
+        }
+        let width = match self {
+            ChainFamily::Evm => 20,
+            ChainFamily::Solana => 32,
+            ChainFamily::Other => return Some(*address),
+        };
+        let mut normalized = [0u8; 64];
+        if address[width..].iter().all(|b| *b == 0) {
+            normalized[..width].copy_from_slice(&address[..width]);
+        } else if self == ChainFamily::Evm
+            && address[..12].iter().all(|b| *b == 0)
+            && address[32..].iter().all(|b| *b == 0)
+        {
+            normalized[..20].copy_from_slice(&address[12..32]);
+        } else {
+            return None;
+        }
+        Some(normalized)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SPL TOKEN
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// The SPL Token program, which holds escrow for token ghosts
+pub const SPL_TOKEN_PROGRAM_ID: Pubkey = solana_program::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Wrapped SOL mint; ghosts for it (or the default key) escrow plain lamports
+pub const NATIVE_MINT: Pubkey = solana_program::pubkey!("So11111111111111111111111111111111111111112");
+
+/// True when `token` means native SOL rather than an SPL mint
+pub fn is_native_token(token: &Pubkey) -> bool {
+    *token == Pubkey::default() || *token == NATIVE_MINT
+}
+
+/// The fields of an SPL token account this program reads
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+impl TokenAccount {
+    pub const LEN: usize = 165;
+
+    /// Parses an initialized (and not frozen) token account
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::LEN || data[108] != 1 {
+            return None;
+        }
+        Some(TokenAccount {
+            mint: Pubkey::try_from(&data[0..32]).ok()?,
+            owner: Pubkey::try_from(&data[32..64]).ok()?,
+            amount: u64::from_le_bytes(data[64..72].try_into().ok()?),
+        })
+    }
+
+    /// Parses `account` if the token program owns it
+    pub fn load(account: &AccountInfo) -> Option<Self> {
+        if account.owner != &SPL_TOKEN_PROGRAM_ID {
+            return None;
+        }
+        Self::parse(&account.data.borrow())
+    }
+}
+
+/// SPL Token `Transfer` of `amount` from `source` to `destination`
+pub fn token_transfer(source: &Pubkey, destination: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+    let mut data = Vec::with_capacity(9);
+    data.push(3);
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: SPL_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// PROOF VERIFICATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Canonical message a validator signs to attest a remote burn for MirrorGhost
+#[allow(clippy::too_many_arguments)]
+pub fn mirror_message(
+    ghost_id: &[u8; 32],
+    source_chain: u64,
+    amount: u64,
+    source_token: &Pubkey,
+    destination_token: &Pubkey,
+    min_out: u64,
+    source_nonce: u64,
+    recipient: &Pubkey,
+    source_ts: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(12 + 32 + 8 + 8 + 32 + 32 + 8 + 8 + 32 + 8);
+    message.extend_from_slice(b"ghost-mirror");
+    message.extend_from_slice(ghost_id);
+    message.extend_from_slice(&source_chain.to_le_bytes());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(source_token.as_ref());
+    message.extend_from_slice(destination_token.as_ref());
+    message.extend_from_slice(&min_out.to_le_bytes());
+    message.extend_from_slice(&source_nonce.to_le_bytes());
+    message.extend_from_slice(recipient.as_ref());
+    message.extend_from_slice(&source_ts.to_le_bytes());
+    message
+}
+
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_SIZE: usize = 14;
+
+fn read_u16(data: &[u8], at: usize) -> Option<usize> {
+    data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+}
+
+/// Public keys whose signature over `message` the ed25519 precompile
+/// instruction `ix` verified. Only signatures whose key, signature and message
+/// all live inside `ix` itself are considered.
+fn ed25519_signers(ix: &Instruction, message: &[u8]) -> Vec<Pubkey> {
+    let mut signers = Vec::new();
+    if ix.program_id != ed25519_program::id() {
+        return signers;
+    }
+    let data = &ix.data;
+    let count = data.first().copied().unwrap_or(0) as usize;
+
+    for i in 0..count {
+        let at = ED25519_OFFSETS_START + i * ED25519_OFFSETS_SIZE;
+        let offsets: Option<Vec<usize>> = (0..7).map(|f| read_u16(data, at + f * 2)).collect();
+        let Some(o) = offsets else { break };
+        let (sig_ix, pk_offset, pk_ix, msg_offset, msg_size, msg_ix) = (o[1], o[2], o[3], o[4], o[5], o[6]);
+        if [sig_ix, pk_ix, msg_ix].iter().any(|&idx| idx != u16::MAX as usize) {
+            continue;
+        }
+        let (Some(pk), Some(signed)) = (
+            data.get(pk_offset..pk_offset + 32),
+            data.get(msg_offset..msg_offset + msg_size),
+        ) else {
+            continue;
+        };
+        if signed == message {
+            if let Ok(key) = Pubkey::try_from(pk) {
+                signers.push(key);
             }
-            GhostInstruction::CreateGhost {
+        }
+    }
+    signers
+}
+
+/// Checks that the instruction immediately before the current one is an
+/// ed25519 precompile verification of `message` by a configured validator
+fn verify_validator_attestation(
+    config: &ProgramConfig,
+    instructions_sysvar: &AccountInfo,
+    message: &[u8],
+) -> ProgramResult {
+    if *instructions_sysvar.key != sysvar::instructions::id() {
+        return Err(GhostError::InvalidProof.into());
+    }
+    let current = sysvar::instructions::load_current_index_checked(instructions_sysvar)?;
+    if current == 0 {
+        msg!("Missing ed25519 attestation instruction");
+        return Err(GhostError::InvalidProof.into());
+    }
+    let ix = sysvar::instructions::load_instruction_at_checked(
+        current as usize - 1,
+        instructions_sysvar,
+    )?;
+    if ed25519_signers(&ix, message).iter().any(|k| config.is_validator(k)) {
+        Ok(())
+    } else {
+        msg!("No validator attestation for mirror message");
+        Err(GhostError::InvalidProof.into())
+    }
+}
+
+/// Fields of a Wormhole core bridge `PostedVAA` account that the bridge reads
+pub struct PostedVaa {
+    pub timestamp: u32,
+    pub sequence: u64,
+    pub emitter_chain: u16,
+    pub emitter_address: [u8; 32],
+    pub payload: Vec<u8>,
+}
+
+impl PostedVaa {
+    /// Parses the borsh `MessageData` that follows the `vaa` magic
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..3)? != b"vaa" {
+            return None;
+        }
+        // version, consistency level, vaa time, signature account, submission time, nonce
+        let timestamp = u32::from_le_bytes(data.get(5..9)?.try_into().ok()?);
+        let mut body = data.get(3 + 1 + 1 + 4 + 32 + 4 + 4..)?;
+        Some(PostedVaa {
+            timestamp,
+            sequence: u64::deserialize(&mut body).ok()?,
+            emitter_chain: u16::deserialize(&mut body).ok()?,
+            emitter_address: <[u8; 32]>::deserialize(&mut body).ok()?,
+            payload: Vec::<u8>::deserialize(&mut body).ok()?,
+        })
+    }
+}
+
+/// Ghost transfer payload emitted by the source-chain bridge
+#[derive(Debug, PartialEq, Eq)]
+pub struct GhostPayload {
+    pub ghost_id: [u8; 32],
+    pub amount: u64,
+    pub source_token: Pubkey,
+    pub destination_token: Pubkey,
+    pub min_out: u64,
+    pub recipient: Pubkey,
+}
+
+impl GhostPayload {
+    pub const LEN: usize = 32 + 8 + 32 + 32 + 8 + 32;
+
+    /// ghost_id (32) | amount (u64 big-endian) | source_token (32) |
+    /// destination_token (32) | min_out (u64 big-endian) | recipient (32).
+    /// Shorter payloads from before the recipient was carried are rejected,
+    /// since a mirror without one could never be minted.
+    pub fn parse(payload: &[u8]) -> Option<Self> {
+        if payload.len() != Self::LEN {
+            return None;
+        }
+        Some(GhostPayload {
+            ghost_id: payload[..32].try_into().ok()?,
+            amount: u64::from_be_bytes(payload[32..40].try_into().ok()?),
+            source_token: Pubkey::try_from(&payload[40..72]).ok()?,
+            destination_token: Pubkey::try_from(&payload[72..104]).ok()?,
+            min_out: u64::from_be_bytes(payload[104..112].try_into().ok()?),
+            recipient: Pubkey::try_from(&payload[112..144]).ok()?,
+        })
+    }
+}
+
+pub struct Processor;
+
+impl Processor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction: GhostInstruction,
+    ) -> ProgramResult {
+        match instruction {
+            GhostInstruction::Initialize {
+                admin,
+                validator_threshold,
+                max_validators,
+            } => Self::initialize(program_id, accounts, admin, validator_threshold, max_validators),
+            GhostInstruction::SetValidator { validator, enabled } => {
+                Self::set_validator(program_id, accounts, validator, enabled)
+            }
+            GhostInstruction::CreateGhost {
                 ghost_id,
                 amount,
                 destination_chain,
                 destination_address,
                 source_token,
                 destination_token,
+                min_out,
+                verify_id,
+                nonce,
             } => Self::create_ghost(
                 program_id,
                 accounts,
@@ -259,6 +1999,8 @@ impl Processor {
                 destination_address,
                 source_token,
                 destination_token,
+                min_out,
+                verify_id.then_some(nonce),
             ),
             GhostInstruction::LockGhost { ghost_id } => {
                 Self::lock_ghost(program_id, accounts, ghost_id)
@@ -274,6 +2016,10 @@ impl Processor {
                 burn_proof,
                 source_token,
                 destination_token,
+                min_out,
+                source_nonce,
+                recipient,
+                source_ts,
             } => Self::mirror_ghost(
                 program_id,
                 accounts,
@@ -283,24 +2029,28 @@ impl Processor {
                 burn_proof,
                 source_token,
                 destination_token,
+                min_out,
+                source_nonce,
+                recipient,
+                source_ts,
             ),
             GhostInstruction::MintGhost {
                 ghost_id,
                 mint_proof,
                 recipient,
             } => Self::mint_ghost(program_id, accounts, ghost_id, mint_proof, recipient),
-            GhostInstruction::AcknowledgeRemote { ghost_id } => {
-                Self::ack_remote(program_id, accounts, ghost_id)
+            GhostInstruction::AcknowledgeRemote { ghost_id, source_chain } => {
+                Self::ack_remote(program_id, accounts, ghost_id, source_chain)
             }
             GhostInstruction::DestroyGhost { ghost_id } => {
                 Self::destroy_ghost(program_id, accounts, ghost_id)
             }
             // Pool instructions
-            GhostInstruction::InitializePool { pool_seed } => {
-                Self::initialize_pool(program_id, accounts, pool_seed)
+            GhostInstruction::InitializePool { pool_seed, dest_token } => {
+                Self::initialize_pool(program_id, accounts, pool_seed, dest_token)
             }
-            GhostInstruction::DepositToPool { amount } => {
-                Self::deposit_to_pool(program_id, accounts, amount)
+            GhostInstruction::DepositToPool { amount, min_shares_out } => {
+                Self::deposit_to_pool(program_id, accounts, amount, min_shares_out)
             }
             GhostInstruction::WithdrawFromPool { shares } => {
                 Self::withdraw_from_pool(program_id, accounts, shares)
@@ -311,554 +2061,3994 @@ impl Processor {
             GhostInstruction::RecordPaymentIntent { intent_id, sender_chain, sender_address, amount, dest_token } => {
                 Self::record_payment_intent(program_id, accounts, intent_id, sender_chain, sender_address, amount, dest_token)
             }
+            GhostInstruction::SetLiquidityCap { liquidity_cap_bps } => {
+                Self::set_liquidity_cap(program_id, accounts, liquidity_cap_bps)
+            }
+            GhostInstruction::RefundGhost { ghost_id, failure_proof } => {
+                Self::refund_ghost(program_id, accounts, ghost_id, failure_proof)
+            }
+            GhostInstruction::MirrorGhostFromVaa { vaa_account } => {
+                Self::mirror_ghost_from_vaa(program_id, accounts, vaa_account)
+            }
+            GhostInstruction::SetVaaEmitter { wormhole_program, emitter_chain, emitter_address, source_chain } => {
+                Self::set_vaa_emitter(program_id, accounts, wormhole_program, emitter_chain, emitter_address, source_chain)
+            }
+            GhostInstruction::GrowConfig { new_max_validators } => {
+                Self::grow_config(program_id, accounts, new_max_validators)
+            }
+            GhostInstruction::SetRateLimit { window_secs, max_volume } => {
+                Self::set_rate_limit(program_id, accounts, window_secs, max_volume)
+            }
+            GhostInstruction::UpdateDestination { ghost_id, destination_address, destination_token } => {
+                Self::update_destination(program_id, accounts, ghost_id, destination_address, destination_token)
+            }
+            GhostInstruction::MigrateAccount { kind } => {
+                Self::migrate_account(program_id, accounts, kind)
+            }
+            GhostInstruction::SetMinSigners { min_signers } => {
+                Self::set_min_signers(program_id, accounts, min_signers)
+            }
+            GhostInstruction::FailGhost { ghost_id } => {
+                Self::fail_ghost(program_id, accounts, ghost_id)
+            }
+            GhostInstruction::CloseGhost { ghost_id } => {
+                Self::close_ghost(program_id, accounts, ghost_id)
+            }
+            GhostInstruction::CheckInvariants { pool_seed } => {
+                Self::check_invariants(program_id, accounts, pool_seed)
+            }
+            GhostInstruction::SetTreasury { treasury } => {
+                Self::set_treasury(program_id, accounts, treasury)
+            }
+            GhostInstruction::UnlockGhost { ghost_id } => {
+                Self::unlock_ghost(program_id, accounts, ghost_id)
+            }
+            GhostInstruction::SetPaused { paused } => {
+                Self::set_paused(program_id, accounts, paused)
+            }
+            GhostInstruction::SetFee { fee_bps } => {
+                Self::set_fee(program_id, accounts, fee_bps)
+            }
+            GhostInstruction::GetConfig => Self::get_config(program_id, accounts),
+            GhostInstruction::SetGuardian { guardian } => {
+                Self::set_guardian(program_id, accounts, guardian)
+            }
+            GhostInstruction::SweepExcessRent { kind } => {
+                Self::sweep_excess_rent(program_id, accounts, kind)
+            }
+            GhostInstruction::SetBurnDelay { burn_delay } => {
+                Self::set_burn_delay(program_id, accounts, burn_delay)
+            }
+            GhostInstruction::SetPoolActive { active } => {
+                Self::set_pool_active(program_id, accounts, active)
+            }
+            GhostInstruction::DrainPool { destination } => {
+                Self::drain_pool(program_id, accounts, destination)
+            }
+            GhostInstruction::SetPoolLimits { min_deposit, max_pool_size, min_withdrawal } => {
+                Self::set_pool_limits(program_id, accounts, min_deposit, max_pool_size, min_withdrawal)
+            }
+            #[cfg(feature = "devnet")]
+            GhostInstruction::DevFastMint { ghost_id, burn_proof, mint_proof, recipient } => {
+                Self::dev_fast_mint(program_id, accounts, ghost_id, burn_proof, mint_proof, recipient)
+            }
+            #[cfg(not(feature = "devnet"))]
+            GhostInstruction::DevFastMint { .. } => {
+                msg!("DevFastMint is not available in this build");
+                Err(GhostError::InvalidInstruction.into())
+            }
+            GhostInstruction::SetLocalChainId { chain_id } => {
+                Self::set_local_chain_id(program_id, accounts, chain_id)
+            }
+            GhostInstruction::SetWithdrawGracePeriod { grace_period } => {
+                Self::set_withdraw_grace_period(program_id, accounts, grace_period)
+            }
+            GhostInstruction::SetChain { chain_id, enabled } => {
+                Self::set_chain(program_id, accounts, chain_id, enabled)
+            }
+            GhostInstruction::CloseIntent { intent_id } => {
+                Self::close_intent(program_id, accounts, intent_id)
+            }
+            GhostInstruction::SetChainFinality { chain_id, finality_delay } => {
+                Self::set_chain_finality(program_id, accounts, chain_id, finality_delay)
+            }
+            GhostInstruction::MergePositions => Self::merge_positions(program_id, accounts),
+            GhostInstruction::SetRelayerBond { min_bond, cooldown } => {
+                Self::set_relayer_bond(program_id, accounts, min_bond, cooldown)
+            }
+            GhostInstruction::PostBond { amount } => {
+                Self::post_bond(program_id, accounts, amount)
+            }
+            GhostInstruction::UnbondRelayer => Self::unbond_relayer(program_id, accounts),
+            GhostInstruction::ReleaseBond => Self::release_bond(program_id, accounts),
+            GhostInstruction::SlashRelayer { relayer, amount } => {
+                Self::slash_relayer(program_id, accounts, relayer, amount)
+            }
+            GhostInstruction::SetIntentCap { intent_cap_bps } => {
+                Self::set_intent_cap(program_id, accounts, intent_cap_bps)
+            }
+            GhostInstruction::SetMaxOpenIntents { max_open_intents } => {
+                Self::set_max_open_intents(program_id, accounts, max_open_intents)
+            }
+            GhostInstruction::SetChainFee { chain_id, fee_bps } => {
+                Self::set_chain_fee(program_id, accounts, chain_id, fee_bps)
+            }
+            GhostInstruction::BatchConfigure { validators, threshold, fee_bps } => {
+                Self::batch_configure(program_id, accounts, validators, threshold, fee_bps)
+            }
+            GhostInstruction::SetBreaker { window_secs, max_volume } => {
+                Self::set_breaker(program_id, accounts, window_secs, max_volume)
+            }
+            GhostInstruction::ResetBreaker => Self::reset_breaker(program_id, accounts),
+            GhostInstruction::SetReservedBuffer { reserved_buffer } => {
+                Self::set_reserved_buffer(program_id, accounts, reserved_buffer)
+            }
+            GhostInstruction::ProposeForceSettle { ghost_id, target_state } => {
+                Self::propose_force_settle(program_id, accounts, ghost_id, target_state)
+            }
+            GhostInstruction::ForceSettle { ghost_id } => {
+                Self::force_settle(program_id, accounts, ghost_id)
+            }
+            GhostInstruction::CancelForceSettle { ghost_id } => {
+                Self::cancel_force_settle(program_id, accounts, ghost_id)
+            }
+            GhostInstruction::Heartbeat => Self::heartbeat(program_id, accounts),
+            GhostInstruction::SetRecipientProgram { program, allowed } => {
+                Self::set_recipient_program(program_id, accounts, program, allowed)
+            }
+            GhostInstruction::SetTokenMetadata { mint, decimals, symbol } => {
+                Self::set_token_metadata(program_id, accounts, mint, decimals, symbol)
+            }
+            GhostInstruction::CancelIntent { intent_id } => {
+                Self::cancel_intent(program_id, accounts, intent_id)
+            }
+            GhostInstruction::SetAdmins { admins, threshold } => {
+                Self::set_admins(program_id, accounts, admins, threshold)
+            }
+            GhostInstruction::SettleAndClose { ghost_id, rent_destination } => {
+                Self::settle_and_close(program_id, accounts, ghost_id, rent_destination)
+            }
+            GhostInstruction::SetChainAddressFamily { chain_id, family } => {
+                Self::set_chain_address_family(program_id, accounts, chain_id, family)
+            }
+        }
+    }
+
+    fn initialize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        admin: Pubkey,
+        validator_threshold: u8,
+        max_validators: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let signer = next_account_info(account_info_iter)?;
+        
+        if !signer.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if config_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        let config = ProgramConfig {
+            admin,
+            validator_threshold,
+            max_validators,
+            ..ProgramConfig::default()
+        };
+
+        Self::save_config(config_account, &config)?;
+
+        msg!("Ghost program initialized");
+        Ok(())
+    }
+
+    fn load_config(
+        program_id: &Pubkey,
+        account: &AccountInfo,
+    ) -> Result<ProgramConfig, ProgramError> {
+        if account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        let config = Self::decode_config(&account.data.borrow())?;
+        if config.admin == Pubkey::default() {
+            msg!("Config account is not initialized");
+            return Err(GhostError::NotInitialized.into());
+        }
+        Ok(config)
+    }
+
+    /// Decodes a config in either layout it has had: slotted, or the bare
+    /// borsh struct from before versioning, which save_config upgrades
+    fn decode_config(data: &[u8]) -> Result<ProgramConfig, ProgramError> {
+        match account_version(data) {
+            Some(CONFIG_VERSION) => Self::parse_config(data),
+            Some(version) => {
+                msg!("Unknown config version {}", version);
+                Err(GhostError::AccountVersionMismatch.into())
+            }
+            // A zeroed account was allocated but Initialize never ran on it
+            None if data.iter().all(|b| *b == 0) => {
+                msg!("Config account is not initialized");
+                Err(GhostError::NotInitialized.into())
+            }
+            None => {
+                let mut slice = data;
+                ProgramConfig::deserialize(&mut slice).map_err(|_| GhostError::AccountDeserialization.into())
+            }
+        }
+    }
+
+    /// Decodes the slotted layout: borsh header, then the validator region
+    fn parse_config(data: &[u8]) -> Result<ProgramConfig, ProgramError> {
+        let mut header = data
+            .get(ACCOUNT_HEADER_LEN..CONFIG_HEADER_LEN)
+            .ok_or(GhostError::AccountDeserialization)?;
+        let mut config = ProgramConfig::deserialize(&mut header).map_err(|e| {
+            msg!("Failed to deserialize config: {:?}", e);
+            GhostError::AccountDeserialization
+        })?;
+        config.validators = config_validators(data)?;
+        Ok(config)
+    }
+
+    /// Deserializes a current-version account through a slice reader, ignoring
+    /// any trailing bytes left over when the account was allocated larger than
+    /// the struct
+    fn load_account<T: BorshDeserialize>(account: &AccountInfo) -> Result<T, ProgramError> {
+        let data = account.data.borrow();
+        if account_version(&data) != Some(ACCOUNT_VERSION) {
+            return Err(GhostError::AccountVersionMismatch.into());
+        }
+        let mut slice: &[u8] = &data[ACCOUNT_HEADER_LEN..];
+        T::deserialize(&mut slice).map_err(|_| GhostError::AccountDeserialization.into())
+    }
+
+    /// Like load_account, but also reads an account still in the bare borsh
+    /// layout it had before versioning. The next save_account upgrades it.
+    fn load_or_legacy<T: LegacyLayout>(account: &AccountInfo) -> Result<T, ProgramError> {
+        if account_version(&account.data.borrow()).is_some() {
+            return Self::load_account(account);
+        }
+        if Self::is_blank(account) {
+            return Err(GhostError::AccountDeserialization.into());
+        }
+        Self::load_legacy(account)
+    }
+
+    /// Writes the account header followed by the borsh body
+    fn save_account<T: BorshSerialize>(account: &AccountInfo, value: &T) -> ProgramResult {
+        let mut data = account.data.borrow_mut();
+        write_account_header(&mut data, ACCOUNT_VERSION)?;
+        value
+            .serialize(&mut &mut data[ACCOUNT_HEADER_LEN..])
+            .map_err(|_| {
+                msg!("Account too small for its current layout; grow it with MigrateAccount");
+                GhostError::AccountSerialization
+            })?;
+        Ok(())
+    }
+
+    /// Bytes `value` needs in the versioned layout
+    fn versioned_len<T: BorshSerialize>(value: &T) -> Result<usize, ProgramError> {
+        let body = value.try_to_vec().map_err(|_| GhostError::AccountSerialization)?;
+        Ok(ACCOUNT_HEADER_LEN + body.len())
+    }
+
+    /// Rejects a handler's state accounts when any two alias the same key,
+    /// since writing both would corrupt one with the other
+    fn ensure_distinct(accounts: &[&AccountInfo]) -> ProgramResult {
+        for (i, account) in accounts.iter().enumerate() {
+            if accounts[i + 1..].iter().any(|other| other.key == account.key) {
+                msg!("Account {} passed more than once", account.key);
+                return Err(GhostError::DuplicateAccount.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// True for an account that has never been written
+    fn is_blank(account: &AccountInfo) -> bool {
+        account.data.borrow().iter().all(|b| *b == 0)
+    }
+
+    /// Grows `account` to at least `len` bytes, with `payer` topping up rent
+    fn ensure_account_len<'a>(
+        account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        len: usize,
+    ) -> ProgramResult {
+        if len <= account.data_len() {
+            return Ok(());
+        }
+        let required = Rent::get()?.minimum_balance(len);
+        let shortfall = required.saturating_sub(account.lamports());
+        if shortfall > 0 {
+            let transfer_ix = solana_program::system_instruction::transfer(
+                payer.key,
+                account.key,
+                shortfall,
+            );
+            solana_program::program::invoke(
+                &transfer_ix,
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        account.realloc(len, true)
+    }
+
+    /// Rewrites the whole config: header (with an empty validator vec) and
+    /// every validator slot
+    fn save_config(account: &AccountInfo, config: &ProgramConfig) -> ProgramResult {
+        config.check_bounds()?;
+        if account.data_len() < ProgramConfig::space(config.validators.len()) {
+            msg!("Config account too small for its current layout; grow it with MigrateAccount");
+            return Err(GhostError::AccountSerialization.into());
+        }
+        Self::write_header_region(account, config)?;
+        for (index, validator) in config.validators.iter().enumerate() {
+            Self::write_validator_slot(account, index, validator)?;
+        }
+        Self::write_validator_count(account, config.validators.len())
+    }
+
+    /// Rewrites only the header region, leaving the validator slots untouched.
+    /// A config still in the legacy layout has no slots yet, so it is
+    /// rewritten whole.
+    fn write_config_header(account: &AccountInfo, config: &ProgramConfig) -> ProgramResult {
+        if account_version(&account.data.borrow()) != Some(CONFIG_VERSION) {
+            return Self::save_config(account, config);
+        }
+        Self::write_header_region(account, config)
+    }
+
+    fn write_header_region(account: &AccountInfo, config: &ProgramConfig) -> ProgramResult {
+        let header = ProgramConfig {
+            validators: Vec::new(),
+            ..config.clone()
+        };
+        let mut data = account.data.borrow_mut();
+        let region = data
+            .get_mut(..CONFIG_HEADER_LEN)
+            .ok_or(GhostError::AccountSerialization)?;
+        // Stale bytes past a shorter header, or left by the legacy layout,
+        // would read back as appended fields
+        region.fill(0);
+        write_account_header(region, CONFIG_VERSION)?;
+        header
+            .serialize(&mut &mut region[ACCOUNT_HEADER_LEN..])
+            .map_err(|_| GhostError::AccountSerialization)?;
+        Ok(())
+    }
+
+    fn write_validator_slot(account: &AccountInfo, index: usize, validator: &Pubkey) -> ProgramResult {
+        let start = CONFIG_HEADER_LEN + 4 + index * 32;
+        let mut data = account.data.borrow_mut();
+        let slot = data
+            .get_mut(start..start + 32)
+            .ok_or(GhostError::AccountSerialization)?;
+        slot.copy_from_slice(validator.as_ref());
+        Ok(())
+    }
+
+    fn write_validator_count(account: &AccountInfo, count: usize) -> ProgramResult {
+        let mut data = account.data.borrow_mut();
+        let region = data
+            .get_mut(CONFIG_HEADER_LEN..CONFIG_HEADER_LEN + 4)
+            .ok_or(GhostError::AccountSerialization)?;
+        region.copy_from_slice(&(count as u32).to_le_bytes());
+        Ok(())
+    }
+
+    /// Without an admin set, `signer` must be the lone admin. With one,
+    /// `signer` must be a member and at least `admin_threshold` distinct
+    /// members must sign somewhere in `accounts`.
+    fn ensure_admin(config: &ProgramConfig, signer: &AccountInfo, accounts: &[AccountInfo]) -> ProgramResult {
+        if config.admins.is_empty() {
+            if !signer.is_signer || signer.key != &config.admin {
+                return Err(GhostError::UnauthorizedAdmin.into());
+            }
+            return Ok(());
+        }
+        if !signer.is_signer || !config.admins.contains(signer.key) {
+            return Err(GhostError::UnauthorizedAdmin.into());
+        }
+        let mut approvals: Vec<&Pubkey> = Vec::new();
+        for account in accounts {
+            if account.is_signer && config.admins.contains(account.key) && !approvals.contains(&account.key) {
+                approvals.push(account.key);
+            }
+        }
+        if approvals.len() < config.admin_threshold as usize {
+            msg!("Admin approvals {} below threshold {}", approvals.len(), config.admin_threshold);
+            return Err(GhostError::UnauthorizedAdmin.into());
+        }
+        Ok(())
+    }
+
+    fn set_validator(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        validator: Pubkey,
+        enabled: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        // Only the touched slot and the count are rewritten, once a legacy
+        // config has been upgraded to have slots
+        if account_version(&config_account.data.borrow()) != Some(CONFIG_VERSION) {
+            Self::save_config(config_account, &config)?;
+        }
+        let count = config.validators.len();
+        if enabled {
+            if !config.is_validator(&validator) {
+                if count >= config.max_validators as usize {
+                    return Err(GhostError::ValidatorLimit.into());
+                }
+                Self::write_validator_slot(config_account, count, &validator)?;
+                Self::write_validator_count(config_account, count + 1)?;
+            }
+        } else if let Some(index) = config.validators.iter().position(|v| v == &validator) {
+            // Swap-remove: the last validator moves into the freed slot
+            let last = count - 1;
+            if last < config.required_signers() {
+                msg!("Removal would leave {} validators for a threshold of {}", last, config.required_signers());
+                return Err(GhostError::InvalidConfig.into());
+            }
+            if index != last {
+                Self::write_validator_slot(config_account, index, &config.validators[last])?;
+            }
+            Self::write_validator_count(config_account, last)?;
+        }
+
+        // Log the count only; the full set can exceed the log limit
+        let active = config_validators(&config_account.data.borrow())?.len();
+        msg!("Validator updated ({} active)", active);
+        Ok(())
+    }
+
+    fn batch_configure(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        validators: Vec<(Pubkey, bool)>,
+        threshold: Option<u8>,
+        fee_bps: Option<u16>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        // Same slot semantics as SetValidator: append, or swap-remove
+        for (validator, enabled) in validators {
+            let position = config.validators.iter().position(|v| v == &validator);
+            match (enabled, position) {
+                (true, None) => config.validators.push(validator),
+                (false, Some(index)) => {
+                    config.validators.swap_remove(index);
+                }
+                _ => {}
+            }
+        }
+        if let Some(threshold) = threshold {
+            config.validator_threshold = threshold;
+        }
+        if let Some(fee_bps) = fee_bps {
+            if fee_bps as u64 > BPS_DENOMINATOR {
+                return Err(GhostError::InvalidConfig.into());
+            }
+            config.fee_bps = fee_bps;
+        }
+
+        // Validate the end state only; intermediate steps never hit the account
+        config.check_bounds()?;
+        if config.required_signers() > config.validators.len() {
+            msg!("{} validators for a threshold of {}", config.validators.len(), config.required_signers());
+            return Err(GhostError::InvalidConfig.into());
+        }
+
+        Self::save_config(config_account, &config)?;
+        msg!(
+            "Config updated ({} validators, threshold {}, fee {} bps)",
+            config.validators.len(),
+            config.validator_threshold,
+            config.fee_bps
+        );
+        Ok(())
+    }
+
+    /// Accounts: config, validator, heartbeat PDA, system program
+    fn heartbeat(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let validator = next_account_info(account_info_iter)?;
+        let heartbeat_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        config.assert_validator(validator.key)?;
+        if !validator.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"heartbeat", validator.key.as_ref()], program_id);
+        if *heartbeat_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if heartbeat_account.data_is_empty() {
+            Self::create_pda_account(
+                program_id,
+                validator,
+                heartbeat_account,
+                system_program,
+                ValidatorHeartbeat::space(),
+                &[b"heartbeat", validator.key.as_ref(), &[bump]],
+            )?;
+        }
+
+        let heartbeat = ValidatorHeartbeat {
+            validator: *validator.key,
+            last_seen_ts: Clock::get()?.unix_timestamp,
+        };
+        Self::save_account(heartbeat_account, &heartbeat)?;
+        Ok(())
+    }
+
+    fn grow_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_max_validators: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if (new_max_validators as usize) < config.validators.len() {
+            msg!("Cannot shrink below {} validators", config.validators.len());
+            return Err(GhostError::ValidatorLimit.into());
+        }
+
+        let new_len = ProgramConfig::space(new_max_validators as usize);
+        Self::ensure_account_len(config_account, admin, system_program, new_len)?;
+
+        config.max_validators = new_max_validators;
+        Self::save_config(config_account, &config)?;
+        msg!("Config grown to {} validators", new_max_validators);
+        Ok(())
+    }
+
+    /// Accounts: config, target, admin, system program. When migrating the
+    /// config itself, pass it as both config and target.
+    fn migrate_account(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        kind: AccountKind,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let target = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if config_account.owner != program_id || target.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        match kind {
+            AccountKind::Config => {
+                if target.key != config_account.key {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                Self::migrate_config(target, admin, system_program)
+            }
+            AccountKind::Ghost => Self::migrate::<GhostAccount>(target, admin, system_program),
+            AccountKind::Pool => Self::migrate_pool(target, admin, system_program),
+            AccountKind::Position => Self::migrate::<LPPosition>(target, admin, system_program),
+            AccountKind::Intent => Self::migrate::<PaymentIntent>(target, admin, system_program),
+            AccountKind::RelayerStats => Self::migrate::<RelayerStats>(target, admin, system_program),
+        }?;
+
+        msg!("Account migrated to version {}", ACCOUNT_VERSION);
+        Ok(())
+    }
+
+    /// Accounts: config, target, admin, treasury. Only the config and
+    /// zero-copy pools are supported; a pool keeps every lamport backing
+    /// `available_liquidity` and `total_fees`, and any donated surplus.
+    fn sweep_excess_rent(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        kind: AccountKind,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let target = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+
+        if target.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if config.treasury == Pubkey::default() || *treasury.key != config.treasury {
+            msg!("Treasury mismatch");
+            return Err(GhostError::InvalidConfig.into());
+        }
+
+        let (used_len, reserved) = match kind {
+            AccountKind::Config => {
+                if target.key != config_account.key {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                (ProgramConfig::space(config.max_validators as usize), 0)
+            }
+            AccountKind::Pool => {
+                if account_version(&target.data.borrow()) != Some(POOL_ZERO_COPY_VERSION) {
+                    msg!("Migrate the pool to the zero-copy layout first");
+                    return Err(GhostError::AccountVersionMismatch.into());
+                }
+                let pool = Self::load_pool(target)?;
+                (LiquidityPool::space(), pool.available_liquidity.saturating_add(pool.total_fees))
+            }
+            _ => return Err(ProgramError::InvalidArgument),
+        };
+
+        let rent_before = Rent::get()?.minimum_balance(target.data_len());
+        if target.data_len() > used_len {
+            target.realloc(used_len, false)?;
+        }
+        let rent = Rent::get()?.minimum_balance(target.data_len());
+        let mut surplus = target.lamports().saturating_sub(rent.saturating_add(reserved));
+        if kind == AccountKind::Pool {
+            // Lamports beyond the pool's books are owed to its LPs, who get
+            // them through absorb_surplus on the next deposit; only the rent
+            // freed by shrinking goes to the treasury
+            surplus = surplus.min(rent_before.saturating_sub(rent));
+        }
+        if surplus > 0 {
+            **target.try_borrow_mut_lamports()? -= surplus;
+            **treasury.try_borrow_mut_lamports()? += surplus;
+        }
+
+        msg!("Swept {} lamports of excess rent", surplus);
+        Ok(())
+    }
+
+    fn migrate_config<'a>(
+        account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        if account_version(&account.data.borrow()) == Some(CONFIG_VERSION) {
+            msg!("Config is already at version {}", CONFIG_VERSION);
+            return Err(GhostError::InvalidState.into());
+        }
+        let config = Self::decode_config(&account.data.borrow())?;
+        let slots = (config.max_validators as usize).max(config.validators.len());
+        Self::ensure_account_len(account, payer, system_program, ProgramConfig::space(slots))?;
+        Self::save_config(account, &config)
+    }
+
+    /// Reads the bare borsh layout accounts had before the account header existed
+    fn load_legacy<T: BorshDeserialize>(account: &AccountInfo) -> Result<T, ProgramError> {
+        let data = account.data.borrow();
+        let mut slice: &[u8] = &data;
+        T::deserialize(&mut slice).map_err(|_| GhostError::AccountDeserialization.into())
+    }
+
+    fn migrate<'a, T: BorshSerialize + LegacyLayout>(
+        account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        // Current-version accounts only need migrating when fields have been
+        // appended since they were allocated
+        let current = account_version(&account.data.borrow()) == Some(ACCOUNT_VERSION);
+        let value: T = Self::load_or_legacy(account)?;
+        let len = Self::versioned_len(&value)?;
+        if current && len <= account.data_len() {
+            msg!("Account is already at version {}", ACCOUNT_VERSION);
+            return Err(GhostError::InvalidState.into());
+        }
+        Self::ensure_account_len(account, payer, system_program, len)?;
+        Self::save_account(account, &value)
+    }
+
+    fn set_min_signers(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        min_signers: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if min_signers as usize > config.validators.len() {
+            msg!("Only {} validators registered", config.validators.len());
+            return Err(GhostError::InvalidConfig.into());
+        }
+        config.min_signers = min_signers;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Minimum signers set to {}", min_signers);
+        Ok(())
+    }
+
+    fn set_treasury(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        treasury: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if treasury == Pubkey::default() {
+            return Err(GhostError::InvalidConfig.into());
+        }
+        config.treasury = treasury;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Treasury set to {}", treasury);
+        Ok(())
+    }
+
+    fn set_paused(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let authority = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        let is_guardian = config.guardian != Pubkey::default()
+            && authority.is_signer
+            && *authority.key == config.guardian;
+        if !is_guardian {
+            Self::ensure_admin(&config, authority, accounts)?;
+        }
+        config.paused = paused;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Paused: {}", paused);
+        Ok(())
+    }
+
+    fn set_guardian(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        guardian: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        config.guardian = guardian;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Guardian set to {}", guardian);
+        Ok(())
+    }
+
+    /// Accounts: config, admin, then any co-signing admins
+    fn set_admins(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        admins: Vec<Pubkey>,
+        threshold: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        for (index, key) in admins.iter().enumerate() {
+            if *key == Pubkey::default() || admins[..index].contains(key) {
+                msg!("Invalid or repeated admin {}", key);
+                return Err(GhostError::InvalidConfig.into());
+            }
+        }
+        config.admins = admins;
+        config.admin_threshold = threshold;
+
+        // check_bounds rejects a threshold the set can't meet
+        Self::save_config(config_account, &config)?;
+        msg!("Admin set: {}-of-{}", threshold, config.admins.len());
+        Ok(())
+    }
+
+    fn set_local_chain_id(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        chain_id: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        config.local_chain_id = chain_id;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Local chain ID set to {}", config.local_chain());
+        Ok(())
+    }
+
+    fn set_burn_delay(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        burn_delay: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if burn_delay < 0 {
+            return Err(GhostError::InvalidConfig.into());
+        }
+        config.burn_delay = burn_delay;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Burn delay set to {}s", burn_delay);
+        Ok(())
+    }
+
+    fn set_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_bps: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if fee_bps as u64 > BPS_DENOMINATOR {
+            return Err(GhostError::InvalidConfig.into());
+        }
+        config.fee_bps = fee_bps;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Fee set to {} bps", fee_bps);
+        Ok(())
+    }
+
+    fn get_config(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        let summary = ConfigSummary::from(&config)
+            .try_to_vec()
+            .map_err(|_| GhostError::AccountSerialization)?;
+        solana_program::program::set_return_data(&summary);
+        Ok(())
+    }
+
+    fn set_liquidity_cap(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        liquidity_cap_bps: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if liquidity_cap_bps as u64 > BPS_DENOMINATOR {
+            return Err(GhostError::InvalidConfig.into());
+        }
+        config.liquidity_cap_bps = liquidity_cap_bps;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Liquidity cap set to {} bps", liquidity_cap_bps);
+        Ok(())
+    }
+
+    /// Accounts: config, ghost, initiator, rent payer, system program, vault
+    /// PDA; for SPL `source_token`s the initiator's token account, the vault's
+    /// escrow token account and the token program; the destination chain's
+    /// registry PDA; then optionally the pool whose liquidity caps the amount
+    #[allow(clippy::too_many_arguments)]
+    fn create_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        amount: u64,
+        destination_chain: u64,
+        destination_address: [u8; 64],
+        source_token: Pubkey,
+        destination_token: Pubkey,
+        min_out: u64,
+        id_nonce: Option<u64>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let ghost_account = next_account_info(account_info_iter)?;
+        let initiator = next_account_info(account_info_iter)?;
+        let rent_payer = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+        let vault_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, ghost_account, vault_account])?;
+        let token_accounts = if is_native_token(&source_token) {
+            None
+        } else {
+            let source = next_account_info(account_info_iter)?;
+            let escrow = next_account_info(account_info_iter)?;
+            let token_program = next_account_info(account_info_iter)?;
+            Self::ensure_distinct(&[source, escrow])?;
+            Some((source, escrow, token_program))
+        };
+        let chain_account = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        config.ensure_not_paused()?;
+        if !initiator.is_signer || !rent_payer.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        // A fresh ghost keypair is allocated here at the rent payer's expense,
+        // so sponsored flows need no SOL from the initiator for rent
+        if ghost_account.owner == &system_program::id() && ghost_account.data_is_empty() {
+            let space = GhostAccount::space();
+            let create_ix = solana_program::system_instruction::create_account(
+                rent_payer.key,
+                ghost_account.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            );
+            solana_program::program::invoke(
+                &create_ix,
+                &[rent_payer.clone(), ghost_account.clone(), system_program.clone()],
+            )?;
+        } else if ghost_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        } else if !Self::is_blank(ghost_account) {
+            // Overwriting would orphan the recorded ghost's vault, which is
+            // keyed by its own ID
+            msg!("Ghost account already holds a ghost");
+            return Err(GhostError::GhostExists.into());
+        }
+        if let Some(nonce) = id_nonce {
+            let expected = client::derive_ghost_id(
+                initiator.key,
+                destination_chain,
+                &destination_address,
+                amount,
+                nonce,
+            );
+            if expected != ghost_id {
+                msg!("Ghost ID does not match its derivation");
+                return Err(GhostError::GhostMismatch.into());
+            }
+        }
+        let destination_address =
+            Self::normalize_destination(program_id, chain_account, destination_chain, &destination_address)?;
+        // A ghost always starts on this chain, so it must leave it
+        if destination_chain == config.local_chain() {
+            msg!("Destination chain {} is the local chain", destination_chain);
+            return Err(GhostError::InvalidRoute.into());
+        }
+
+        // Optional pool account: refuse amounts the pool could never settle
+        if let Some(pool_account) = account_info_iter.next() {
+            if pool_account.owner != program_id {
+                return Err(GhostError::IncorrectProgramId.into());
+            }
+            let pool = Self::load_pool(pool_account)?;
+            let cap = config.liquidity_cap(pool.available_liquidity);
+            if amount > cap {
+                msg!("Amount exceeds pool liquidity cap: {} > {}", amount, cap);
+                return Err(GhostError::InsufficientLiquidity.into());
+            }
+        }
+
+        let ghost = GhostAccount {
+            ghost_id,
+            initiator: *initiator.key,
+            source_token,
+            destination_token,
+            destination_chain,
+            destination_address,
+            state: GhostState::Created,
+            amount,
+            lock_ts: Clock::get()?.unix_timestamp,
+            burn_ts: 0,
+            mint_ts: 0,
+            burn_proof: [0u8; 32],
+            mint_proof: [0u8; 32],
+            is_remote: false,
+            remote_ack: false,
+            min_out,
+            escrow_vault: true,
+            fee: Some(config.fee_for(amount)),
+            token_escrow: token_accounts.is_some(),
+            ..GhostAccount::default()
+        };
+
+        Self::store_ghost(ghost_account, &ghost, rent_payer, accounts)?;
+
+        // Escrow the source SOL in the ghost's vault PDA until it settles or is
+        // refunded; the rent payer funds the vault's own rent
+        let (expected, bump) = Pubkey::find_program_address(&[b"vault", &ghost_id], program_id);
+        if *vault_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if vault_account.owner == program_id {
+            msg!("Vault for this ghost already exists");
+            return Err(GhostError::GhostExists.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            rent_payer,
+            vault_account,
+            system_program,
+            0,
+            &[b"vault", &ghost_id, &[bump]],
+        )?;
+        match token_accounts {
+            None => {
+                let transfer_ix = solana_program::system_instruction::transfer(
+                    initiator.key,
+                    vault_account.key,
+                    amount,
+                );
+                solana_program::program::invoke(
+                    &transfer_ix,
+                    &[initiator.clone(), vault_account.clone(), system_program.clone()],
+                )?;
+            }
+            Some((source, escrow, token_program)) => {
+                Self::escrow_tokens(&ghost, initiator, vault_account, source, escrow, token_program)?;
+            }
+        }
+
+        // Global creation sequence; never decremented so indexers can spot gaps
+        config.total_ghosts_created += 1;
+        config.ghost_counts.record_transition(GhostState::None, GhostState::Created);
+        Self::save_config(config_account, &config)?;
+
+        msg!("Ghost created (#{}), fee {}", config.total_ghosts_created, config.fee_for(amount));
+        Ok(())
+    }
+
+    /// Accounts: config, ghost, initiator, destination chain registry PDA,
+    /// then the system program if the ghost predates the current layout
+    fn update_destination(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        destination_address: [u8; 64],
+        destination_token: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let ghost_account = next_account_info(account_info_iter)?;
+        let initiator = next_account_info(account_info_iter)?;
+        let chain_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, ghost_account])?;
+
+        let _config = Self::load_config(program_id, config_account)?;
+        if !initiator.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if ghost_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        let mut ghost: GhostAccount = Self::load_or_legacy(ghost_account)?;
+        if ghost.ghost_id != ghost_id {
+            return Err(GhostError::GhostMismatch.into());
+        }
+        if ghost.initiator != *initiator.key {
+            return Err(GhostError::InitiatorMismatch.into());
+        }
+        // Once locked the transfer is in flight and the destination is final
+        if ghost.state != GhostState::Created {
+            return Err(GhostError::InvalidState.into());
+        }
+        let destination_address =
+            Self::normalize_destination(program_id, chain_account, ghost.destination_chain, &destination_address)?;
+
+        ghost.destination_address = destination_address;
+        ghost.destination_token = destination_token;
+        Self::store_ghost(ghost_account, &ghost, initiator, accounts)?;
+
+        msg!("Ghost destination updated");
+        Ok(())
+    }
+
+    /// Rejects destinations malformed for the target chain's address family
+    /// in the chain's registry entry
+    fn normalize_destination(
+        program_id: &Pubkey,
+        chain_account: &AccountInfo,
+        destination_chain: u64,
+        destination_address: &[u8; 64],
+    ) -> Result<[u8; 64], ProgramError> {
+        let chain = Self::load_chain(program_id, chain_account, destination_chain)?;
+        let family = chain.address_family.ok_or_else(|| {
+            msg!("Chain {} has no address family set", destination_chain);
+            GhostError::InvalidDestination
+        })?;
+        family
+            .normalize_address(destination_address)
+            .ok_or_else(|| GhostError::InvalidDestination.into())
+    }
+
+    fn lock_ghost(program_id: &Pubkey, accounts: &[AccountInfo], ghost_id: [u8; 32]) -> ProgramResult {
+        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        if ghost.state != GhostState::Created {
+            return Err(GhostError::InvalidState.into());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        Self::ensure_monotonic(ghost.lock_ts, now)?;
+        ghost.state = GhostState::Locked;
+        ghost.lock_ts = now;
+        Self::write_ghost(accounts, ghost)?;
+        let _ = config;
+        msg!("Ghost locked");
+        Ok(())
+    }
+
+    /// Roll a Locked ghost back to Created. Accounts: config, ghost, validator,
+    /// then co-signing validators.
+    fn unlock_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> ProgramResult {
+        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        if ghost.state != GhostState::Locked || ghost.burn_ts != 0 {
+            return Err(GhostError::InvalidState.into());
+        }
+        if ghost.burn_proof != [0u8; 32] {
+            msg!("Burn proof already recorded");
+            return Err(GhostError::InvalidState.into());
+        }
+        Self::ensure_threshold(&config, &accounts[2..])?;
+
+        ghost.state = GhostState::Created;
+        Self::write_ghost(accounts, ghost)?;
+        msg!("Ghost unlocked");
+        Ok(())
+    }
+
+    fn burn_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        burn_proof: [u8; 32],
+    ) -> ProgramResult {
+        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        if ghost.state != GhostState::Locked {
+            return Err(GhostError::InvalidState.into());
+        }
+        // Veto window: the lock can still be undone with UnlockGhost
+        let now = Clock::get()?.unix_timestamp;
+        Self::ensure_monotonic(ghost.lock_ts, now)?;
+        if now < ghost.lock_ts.saturating_add(config.burn_delay) {
+            msg!("Burn allowed after {}", ghost.lock_ts.saturating_add(config.burn_delay));
+            return Err(GhostError::TooEarly.into());
+        }
+        // Accounts: config, ghost, validator, snapshot PDA, system program,
+        // then co-signing validators
+        Self::ensure_threshold(&config, &accounts[2..])?;
+        let validator = &accounts[2];
+        let snapshot_account = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let system_program = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        Self::snapshot_validators(program_id, &config, &mut ghost, validator, snapshot_account, system_program)?;
+
+        ghost.state = GhostState::Burned;
+        ghost.burn_ts = now;
+        ghost.burn_proof = burn_proof;
+        Self::write_ghost(accounts, ghost)?;
+        msg!("Ghost burned");
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn mirror_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        source_chain: u64,
+        amount: u64,
+        burn_proof: [u8; 32],
+        source_token: Pubkey,
+        destination_token: Pubkey,
+        min_out: u64,
+        source_nonce: u64,
+        recipient: Pubkey,
+        source_ts: i64,
+    ) -> ProgramResult {
+        let (config, existing) = Self::load_slot_with_validator(program_id, accounts, ghost_id)?;
+        config.ensure_not_paused()?;
+        if recipient == Pubkey::default() {
+            msg!("Mirrored burn names no recipient");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if let Some(ghost) = existing {
+            // Relayers deliver at least once: a repeat of the same burn is a
+            // no-op, anything conflicting with the recorded mirror is rejected
+            let duplicate = ghost.is_remote
+                && ghost.burn_proof == burn_proof
+                && ghost.amount == amount
+                && ghost.destination_address[..32] == recipient.to_bytes();
+            if duplicate {
+                msg!("Ghost already mirrored");
+                return Ok(());
+            }
+            return Err(GhostError::GhostExists.into());
+        }
+        let mut ghost = GhostAccount::default();
+
+        // Accounts: config, ghost, validator, instructions sysvar, source
+        // chain registry PDA, system program
+        let validator = &accounts[2];
+        let instructions_sysvar = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let chain_account = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let system_program = accounts.get(5).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let message = mirror_message(
+            &ghost_id,
+            source_chain,
+            amount,
+            &source_token,
+            &destination_token,
+            min_out,
+            source_nonce,
+            &recipient,
+            source_ts,
+        );
+        verify_validator_attestation(&config, instructions_sysvar, &message)?;
+
+        // Burns from a chain are mirrored in order, so a gap in the nonces
+        // is a missed mirror and a repeat is a replay
+        let mut chain = Self::load_chain(program_id, chain_account, source_chain)?;
+        if source_nonce <= chain.last_nonce {
+            msg!("Nonce {} from chain {} is not after {}", source_nonce, source_chain, chain.last_nonce);
+            return Err(GhostError::NonceOutOfOrder.into());
+        }
+        if source_nonce != chain.last_nonce + 1 {
+            msg!("Nonces {}..{} from chain {} skipped", chain.last_nonce + 1, source_nonce, source_chain);
+        }
+        chain.last_nonce = source_nonce;
+        // Entries registered before nonces were tracked are too short for them
+        Self::ensure_account_len(chain_account, validator, system_program, ChainConfig::space())?;
+        Self::save_account(chain_account, &chain)?;
+
+        Self::apply_mirror(
+            &mut ghost,
+            ghost_id,
+            source_chain,
+            amount,
+            burn_proof,
+            source_token,
+            destination_token,
+            recipient,
+        )?;
+        ghost.min_out = min_out;
+        ghost.fee = Some(config.fee_for(amount));
+        ghost.required_signers = config.required_signers() as u8;
+        // Finality counts from the attested source burn, which can't be
+        // later than its mirror
+        if source_ts <= 0 {
+            msg!("Mirrored burn has no source timestamp");
+            return Err(ProgramError::InvalidArgument);
+        }
+        ghost.source_ts = source_ts;
+        Self::ensure_monotonic(ghost.source_ts, ghost.burn_ts)?;
+        Self::write_ghost(accounts, ghost)?;
+        msg!("Ghost mirrored from remote chain");
+        Ok(())
+    }
+
+    /// Mirror a remote burn attested by a Wormhole VAA instead of a validator
+    /// signature. Accounts: config, ghost, validator, posted VAA account, VAA
+    /// claim PDA, system program.
+    ///
+    /// Each emitter sequence is claimed once through a PDA at [b"vaa",
+    /// emitter_chain, emitter_address, sequence], so VAAs may be posted in
+    /// any order but none can be consumed twice.
+    fn mirror_ghost_from_vaa(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        vaa_account: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let _ghost_account = next_account_info(account_info_iter)?;
+        let validator = next_account_info(account_info_iter)?;
+        let vaa_info = next_account_info(account_info_iter)?;
+        let claim_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        config.ensure_not_paused()?;
+        if *vaa_info.key != vaa_account {
+            return Err(GhostError::InvalidProof.into());
+        }
+        if config.wormhole_program == Pubkey::default() || vaa_info.owner != &config.wormhole_program {
+            return Err(GhostError::UnknownEmitter.into());
+        }
+
+        let vaa = PostedVaa::parse(&vaa_info.data.borrow()).ok_or(GhostError::InvalidProof)?;
+        if vaa.emitter_chain != config.vaa_emitter_chain
+            || vaa.emitter_address != config.vaa_emitter_address
+        {
+            msg!("VAA from unknown emitter on chain {}", vaa.emitter_chain);
+            return Err(GhostError::UnknownEmitter.into());
+        }
+        // Wormhole numbers chains differently from the bridge
+        if config.vaa_source_chain == 0 {
+            msg!("No bridge chain configured for Wormhole chain {}", vaa.emitter_chain);
+            return Err(GhostError::UnknownEmitter.into());
+        }
+        let payload = GhostPayload::parse(&vaa.payload).ok_or(GhostError::InvalidProof)?;
+        if payload.recipient == Pubkey::default() {
+            msg!("Mirrored burn names no recipient");
+            return Err(GhostError::InvalidProof.into());
+        }
+
+        // The VAA account itself is the burn proof
+        let burn_proof = vaa_account.to_bytes();
+        if let Some(ghost) = Self::load_slot_with_validator(program_id, accounts, payload.ghost_id)?.1 {
+            // Same rule as mirror_ghost: a resubmitted VAA is a no-op,
+            // anything conflicting with the recorded mirror is rejected
+            let duplicate = ghost.is_remote
+                && ghost.burn_proof == burn_proof
+                && ghost.amount == payload.amount
+                && ghost.destination_address[..32] == payload.recipient.to_bytes();
+            if duplicate {
+                msg!("Ghost already mirrored");
+                return Ok(());
+            }
+            return Err(GhostError::GhostExists.into());
+        }
+
+        let sequence = vaa.sequence.to_le_bytes();
+        let chain = vaa.emitter_chain.to_le_bytes();
+        let (expected, bump) = Pubkey::find_program_address(
+            &[b"vaa", &chain, &vaa.emitter_address, &sequence],
+            program_id,
+        );
+        if *claim_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if !claim_account.data_is_empty() {
+            msg!("VAA sequence {} already consumed", vaa.sequence);
+            return Err(GhostError::VaaReplayed.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            validator,
+            claim_account,
+            system_program,
+            VaaClaim::space(),
+            &[b"vaa", &chain, &vaa.emitter_address, &sequence, &[bump]],
+        )?;
+        let claim = VaaClaim {
+            sequence: vaa.sequence,
+            ghost_id: payload.ghost_id,
+        };
+        Self::save_account(claim_account, &claim)?;
+
+        let mut ghost = GhostAccount::default();
+        Self::apply_mirror(
+            &mut ghost,
+            payload.ghost_id,
+            config.vaa_source_chain,
+            payload.amount,
+            burn_proof,
+            payload.source_token,
+            payload.destination_token,
+            payload.recipient,
+        )?;
+        ghost.min_out = payload.min_out;
+        ghost.fee = Some(config.fee_for(payload.amount));
+        ghost.required_signers = config.required_signers() as u8;
+        ghost.source_ts = vaa.timestamp as i64;
+        Self::ensure_monotonic(ghost.source_ts, ghost.burn_ts)?;
+        Self::write_ghost(accounts, ghost)?;
+
+        msg!("Ghost mirrored from VAA sequence {}", vaa.sequence);
+        Ok(())
+    }
+
+    /// Records a remote burn on the ghost; shared by both mirror paths. The
+    /// attested recipient becomes the destination MintGhost must pay.
+    #[allow(clippy::too_many_arguments)]
+    fn apply_mirror(
+        ghost: &mut GhostAccount,
+        ghost_id: [u8; 32],
+        source_chain: u64,
+        amount: u64,
+        burn_proof: [u8; 32],
+        source_token: Pubkey,
+        destination_token: Pubkey,
+        recipient: Pubkey,
+    ) -> ProgramResult {
+        ghost.ghost_id = ghost_id;
+        ghost.initiator = Pubkey::default();
+        ghost.source_token = source_token;
+        ghost.destination_token = destination_token;
+        ghost.destination_chain = source_chain;
+        ghost.destination_address = [0u8; 64];
+        ghost.destination_address[..32].copy_from_slice(recipient.as_ref());
+        ghost.state = GhostState::Burned;
+        ghost.amount = amount;
+        let now = Clock::get()?.unix_timestamp;
+        Self::ensure_monotonic(ghost.burn_ts, now)?;
+        ghost.burn_ts = now;
+        ghost.burn_proof = burn_proof;
+        ghost.is_remote = true;
+        Ok(())
+    }
+
+    fn set_vaa_emitter(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        wormhole_program: Pubkey,
+        emitter_chain: u16,
+        emitter_address: [u8; 32],
+        source_chain: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if source_chain == 0 {
+            msg!("VAA emitter needs a bridge chain id");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Claims are keyed by emitter, so a new emitter starts with none
+        config.wormhole_program = wormhole_program;
+        config.vaa_emitter_chain = emitter_chain;
+        config.vaa_emitter_address = emitter_address;
+        config.vaa_source_chain = source_chain;
+
+        Self::save_config(config_account, &config)?;
+        msg!("VAA emitter set for chain {}", emitter_chain);
+        Ok(())
+    }
+
+    fn mint_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        mint_proof: [u8; 32],
+        recipient: Pubkey,
+    ) -> ProgramResult {
+        // Accounts: config, ghost, validator, snapshot PDA (if the ghost has
+        // one), then co-signing validators; mirrored ghosts also pass their
+        // source chain's registry PDA
+        let (config, mut ghost) = Self::load_ghost(program_id, accounts, ghost_id)?;
+        config.ensure_not_paused()?;
+        if ghost.state != GhostState::Burned {
+            return Err(GhostError::InvalidState.into());
+        }
+        Self::ensure_attested(program_id, &config, &ghost, accounts, None)?;
+        if ghost.is_remote {
+            Self::ensure_source_final(program_id, &ghost, accounts)?;
+        }
+        Self::apply_mint(&config, &mut ghost, mint_proof, recipient)?;
+
+        Self::write_ghost(accounts, ghost)?;
+        msg!("Ghost minted");
+        Ok(())
+    }
+
+    /// Mirrored burns must age past their source chain's finality delay so a
+    /// reorg on the source chain can't leave a mint without a burn. The chain
+    /// registry PDA may be passed anywhere after the validator account.
+    fn ensure_source_final(
+        program_id: &Pubkey,
+        ghost: &GhostAccount,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"chain", &ghost.destination_chain.to_le_bytes()],
+            program_id,
+        );
+        let chain_account = accounts
+            .iter()
+            .skip(3)
+            .find(|account| *account.key == expected)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let chain = Self::load_chain(program_id, chain_account, ghost.destination_chain)?;
+
+        let final_at = ghost.source_ts.saturating_add(chain.finality_delay);
+        if Clock::get()?.unix_timestamp < final_at {
+            msg!("Source burn not final until {}", final_at);
+            return Err(GhostError::TooEarly.into());
+        }
+        Ok(())
+    }
+
+    /// Checks and records a mint on a Burned ghost; shared by MintGhost and
+    /// the devnet fast path
+    fn apply_mint(
+        config: &ProgramConfig,
+        ghost: &mut GhostAccount,
+        mint_proof: [u8; 32],
+        recipient: Pubkey,
+    ) -> ProgramResult {
+        // Only mirrored burns pay out here; a local ghost mints on its
+        // destination chain and settles on that chain's acknowledgement
+        if !ghost.is_remote {
+            msg!("Local ghosts mint on their destination chain");
+            return Err(GhostError::InvalidState.into());
+        }
+        // The mint attests a different event than the burn
+        if mint_proof == [0u8; 32] || mint_proof == ghost.burn_proof {
+            return Err(GhostError::InvalidProof.into());
+        }
+
+        // Slippage guard: a short delivery must go through refund instead
+        let delivered = ghost.delivered_amount(config);
+        if delivered < ghost.min_out {
+            msg!("Delivery below min_out: {} < {}", delivered, ghost.min_out);
+            return Err(GhostError::SlippageExceeded.into());
+        }
+
+        // Pay only the recipient attested with the mirrored burn; a ghost with
+        // none recorded cannot be minted here
+        if recipient == Pubkey::default() || ghost.destination_address[..32] != recipient.to_bytes() {
+            msg!("Recipient does not match recorded destination");
+            return Err(GhostError::RecipientMismatch.into());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        Self::ensure_monotonic(ghost.burn_ts, now)?;
+        ghost.state = GhostState::Minted;
+        ghost.mint_ts = now;
+        ghost.mint_proof = mint_proof;
+        Ok(())
+    }
+
+    /// Burn then mint a Locked ghost with one validator signature. Only
+    /// compiled with the `devnet` feature and only for single-signer configs.
+    /// Accounts: config, ghost, validator.
+    #[cfg(feature = "devnet")]
+    fn dev_fast_mint(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        burn_proof: [u8; 32],
+        mint_proof: [u8; 32],
+        recipient: Pubkey,
+    ) -> ProgramResult {
+        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        config.ensure_not_paused()?;
+        if config.required_signers() > 1 {
+            msg!("DevFastMint needs a single-signer config");
+            return Err(GhostError::ThresholdNotMet.into());
+        }
+        if ghost.state != GhostState::Locked {
+            return Err(GhostError::InvalidState.into());
+        }
+        if burn_proof == [0u8; 32] {
+            return Err(GhostError::InvalidProof.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        Self::ensure_monotonic(ghost.lock_ts, now)?;
+        ghost.state = GhostState::Burned;
+        ghost.burn_ts = now;
+        ghost.burn_proof = burn_proof;
+        Self::apply_mint(&config, &mut ghost, mint_proof, recipient)?;
+
+        Self::write_ghost(accounts, ghost)?;
+        msg!("Ghost burned and minted (devnet)");
+        Ok(())
+    }
+
+    fn ack_remote(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        source_chain: u64,
+    ) -> ProgramResult {
+        // Accounts: config, ghost, validator, snapshot PDA (if the ghost has
+        // one), chain registry PDA
+        let (config, mut ghost) = Self::load_ghost(program_id, accounts, ghost_id)?;
+        if ghost.state != GhostState::Burned {
+            return Err(GhostError::InvalidState.into());
+        }
+        Self::ensure_attested(program_id, &config, &ghost, accounts, Some(1))?;
+
+        // The ack must come from the chain the ghost is bridged with
+        if source_chain != ghost.destination_chain {
+            msg!("Ack from chain {} but ghost is bridged with {}", source_chain, ghost.destination_chain);
+            return Err(GhostError::InvalidRoute.into());
+        }
+        let chain_index = if ghost.validator_set_hash == [0u8; 32] { 3 } else { 4 };
+        let chain_account = accounts.get(chain_index).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        Self::ensure_chain_enabled(program_id, chain_account, source_chain)?;
+        ghost.remote_ack = true;
+        Self::write_ghost(accounts, ghost)?;
+        msg!("Remote mint acknowledged");
+        Ok(())
+    }
+
+    /// Settle a ghost. Accounts: config, ghost, validator, treasury, vault PDA.
+    /// A local ghost's escrow, fee included, is released to the configured
+    /// treasury; see `GhostAccount::delivered_amount`.
+    fn destroy_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> ProgramResult {
+        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        // Mirrored ghosts finish by minting here. Local ghosts are minted on
+        // their destination chain, so, as in the EVM GhostWallet, they finish
+        // once that chain acknowledges the mint of their burn. Local ghosts
+        // minted here before MintGhost refused them settle as they are.
+        let finished = if ghost.is_remote {
+            ghost.state == GhostState::Minted
+        } else {
+            (ghost.state == GhostState::Burned && ghost.remote_ack) || ghost.state == GhostState::Minted
+        };
+        if !finished {
+            return Err(GhostError::InvalidState.into());
+        }
+        let is_remote = ghost.is_remote;
+        let (delivered, fee) = (ghost.delivered_amount(&config), ghost.settlement_fee(&config));
+        let escrow = Self::escrow_account(program_id, &ghost, accounts)?;
+        let (ghost_id, mint, token_escrow) = (ghost.ghost_id, ghost.source_token, ghost.token_escrow);
+        ghost.state = GhostState::Settled;
+        Self::write_ghost(accounts, ghost)?;
+
+        let escrowed = if is_remote {
+            0
+        } else {
+            let treasury = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if config.treasury == Pubkey::default() || *treasury.key != config.treasury {
+                msg!("Treasury mismatch");
+                return Err(GhostError::InvalidConfig.into());
+            }
+            if token_escrow {
+                let tokens = Self::release_token_escrow(program_id, &ghost_id, &mint, escrow, accounts, &config.treasury)?;
+                msg!("{} tokens of {} to treasury", tokens, mint);
+            }
+            Self::release_escrow(escrow, treasury)?
+        };
+        msg!(
+            "Ghost destroyed/settled: {} lamports to treasury ({} delivered, {} fee)",
+            escrowed,
+            delivered,
+            fee
+        );
+        Ok(())
+    }
+
+    /// Refund a burned local ghost after the remote mint failed.
+    ///
+    /// Accounts: config, ghost, validator, initiator, vault PDA, then any
+    /// additional validator signers needed to reach `validator_threshold`.
+    /// Token ghosts also pass the escrow and initiator token accounts and the
+    /// token program. The failure attestation is kept in `mint_proof`, which a refunded
+    /// ghost never otherwise uses.
+    fn refund_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        failure_proof: [u8; 32],
+    ) -> ProgramResult {
+        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        let account_info_iter = &mut accounts.iter();
+        let _config_account = next_account_info(account_info_iter)?;
+        let _ghost_account = next_account_info(account_info_iter)?;
+        let _validator = next_account_info(account_info_iter)?;
+        let initiator = next_account_info(account_info_iter)?;
+
+        if ghost.state != GhostState::Burned || ghost.is_remote || ghost.remote_ack {
+            return Err(GhostError::InvalidState.into());
+        }
+        if failure_proof == [0u8; 32] || failure_proof == ghost.burn_proof {
+            return Err(GhostError::InvalidProof.into());
+        }
+        if *initiator.key != ghost.initiator {
+            return Err(GhostError::InitiatorMismatch.into());
+        }
+        Self::ensure_threshold(&config, &accounts[2..])?;
+
+        let escrow = Self::escrow_account(program_id, &ghost, accounts)?;
+        let (mint, token_escrow) = (ghost.source_token, ghost.token_escrow);
+        ghost.state = GhostState::Refunded;
+        ghost.mint_proof = failure_proof;
+        Self::write_ghost(accounts, ghost)?;
+
+        if token_escrow {
+            let tokens = Self::release_token_escrow(program_id, &ghost_id, &mint, escrow, accounts, initiator.key)?;
+            msg!("{} tokens of {} returned", tokens, mint);
+        }
+        let escrowed = Self::release_escrow(escrow, initiator)?;
+        msg!("Ghost refunded: {} lamports returned", escrowed);
+        Ok(())
+    }
+
+    /// Accounts: config, ghost, validator, treasury (unused for mirrored
+    /// ghosts), rent destination, vault PDA, then any token accounts the
+    /// settlement needs. The same rules apply as for the two separate steps.
+    fn settle_and_close(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        rent_destination: Pubkey,
+    ) -> ProgramResult {
+        let rent_account = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if *rent_account.key != rent_destination {
+            msg!("Rent destination mismatch");
+            return Err(GhostError::InvalidRecipient.into());
+        }
+        Self::destroy_ghost(program_id, accounts, ghost_id)?;
+
+        // CloseGhost expects the rent destination where DestroyGhost has the treasury
+        let close_accounts = [&accounts[..3], &accounts[4..]].concat();
+        Self::close_ghost(program_id, &close_accounts, ghost_id)
+    }
+
+    /// Move an in-flight ghost to Failed. Accounts: config, ghost, validator,
+    /// initiator (ignored for remote ghosts), vault PDA (local ghosts only),
+    /// then co-signing validators; token ghosts add their token accounts as
+    /// for RefundGhost.
+    fn fail_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> ProgramResult {
+        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        let account_info_iter = &mut accounts.iter();
+        let _config_account = next_account_info(account_info_iter)?;
+        let _ghost_account = next_account_info(account_info_iter)?;
+        let _validator = next_account_info(account_info_iter)?;
+        let initiator = next_account_info(account_info_iter)?;
+
+        // An acknowledged remote mint means value was delivered
+        if !ghost.state.can_fail() || ghost.remote_ack {
+            return Err(GhostError::InvalidState.into());
+        }
+        if !ghost.is_remote && *initiator.key != ghost.initiator {
+            return Err(GhostError::InitiatorMismatch.into());
+        }
+        Self::ensure_threshold(&config, &accounts[2..])?;
+
+        let is_remote = ghost.is_remote;
+        let escrow = Self::escrow_account(program_id, &ghost, accounts)?;
+        let (mint, token_escrow) = (ghost.source_token, ghost.token_escrow);
+        ghost.state = GhostState::Failed;
+        Self::write_ghost(accounts, ghost)?;
+
+        let escrowed = if is_remote {
+            0
+        } else {
+            if token_escrow {
+                let tokens = Self::release_token_escrow(program_id, &ghost_id, &mint, escrow, accounts, initiator.key)?;
+                msg!("{} tokens of {} returned", tokens, mint);
+            }
+            Self::release_escrow(escrow, initiator)?
+        };
+        msg!("Ghost failed: {} lamports returned", escrowed);
+        Ok(())
+    }
+
+    /// Close a terminal ghost. Accounts: config, ghost, validator, rent
+    /// destination, which must be the initiator for local ghosts, then the
+    /// vault PDA for ghosts escrowed in one. A remote ghost has no initiator
+    /// to repay, so choosing where its rent goes takes the validator
+    /// threshold: co-signing validators follow.
+    fn close_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> ProgramResult {
+        let (mut config, ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let ghost_account = next_account_info(account_info_iter)?;
+        let _validator = next_account_info(account_info_iter)?;
+        let rent_destination = next_account_info(account_info_iter)?;
+
+        if !ghost.state.is_terminal() {
+            return Err(GhostError::InvalidState.into());
+        }
+        if ghost.is_remote {
+            Self::ensure_threshold(&config, &accounts[2..])?;
+        } else if *rent_destination.key != ghost.initiator {
+            return Err(GhostError::InitiatorMismatch.into());
+        }
+
+        let mut lamports = ghost_account.lamports();
+        if ghost.escrow_vault {
+            let vault = Self::escrow_account(program_id, &ghost, accounts)?;
+            lamports += vault.lamports();
+            **vault.try_borrow_mut_lamports()? = 0;
+        }
+        **ghost_account.try_borrow_mut_lamports()? = 0;
+        **rent_destination.try_borrow_mut_lamports()? += lamports;
+        ghost_account.data.borrow_mut().fill(0);
+        events::GhostEvent {
+            previous: events::state_hash(Some(&ghost)),
+            ghost: None,
+        }
+        .emit();
+
+        config.ghost_counts.record_transition(ghost.state, GhostState::None);
+        Self::write_config_header(config_account, &config)?;
+
+        msg!("Ghost closed: {} lamports reclaimed", lamports);
+        Ok(())
+    }
+
+    /// Accounts: config, ghost, admin, pending PDA, system program
+    fn propose_force_settle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+        target_state: GhostState,
+    ) -> ProgramResult {
+        let (config, ghost) = Self::load_ghost(program_id, accounts, ghost_id)?;
+        let admin = &accounts[2];
+        Self::ensure_admin(&config, admin, accounts)?;
+        let pending_account = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let system_program = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        if ghost.state == GhostState::None || ghost.state.is_terminal() {
+            return Err(GhostError::InvalidState.into());
+        }
+        if !target_state.is_terminal() {
+            msg!("Force settle must target a terminal state");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (expected, bump) = Pubkey::find_program_address(&[b"force_settle", &ghost_id], program_id);
+        if *pending_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if !pending_account.data_is_empty() {
+            msg!("Force settle already pending");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        Self::create_pda_account(
+            program_id,
+            admin,
+            pending_account,
+            system_program,
+            PendingForceSettle::space(),
+            &[b"force_settle", &ghost_id, &[bump]],
+        )?;
+        let pending = PendingForceSettle {
+            ghost_id,
+            target_state,
+            proposed_at: Clock::get()?.unix_timestamp,
+            proposer: *admin.key,
+        };
+        Self::save_account(pending_account, &pending)?;
+
+        msg!(
+            "Force settle to {:?} proposed, executable at {}",
+            target_state,
+            pending.proposed_at + FORCE_SETTLE_TIMELOCK
+        );
+        Ok(())
+    }
+
+    /// Accounts: config, ghost, admin, pending PDA, escrow destination (the
+    /// treasury when settling, else the initiator; local ghosts only), vault
+    /// PDA for ghosts escrowed in one. The pending record's rent goes to the
+    /// admin.
+    fn force_settle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> ProgramResult {
+        let (config, mut ghost) = Self::load_ghost(program_id, accounts, ghost_id)?;
+        Self::ensure_admin(&config, &accounts[2], accounts)?;
+        let pending_account = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let pending = Self::load_pending_force_settle(program_id, pending_account, &ghost_id)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let ready_at = pending.proposed_at.saturating_add(FORCE_SETTLE_TIMELOCK);
+        if now < ready_at {
+            msg!("Force settle timelocked until {}", ready_at);
+            return Err(GhostError::TooEarly.into());
+        }
+        if ghost.state.is_terminal() {
+            return Err(GhostError::InvalidState.into());
+        }
+
+        let is_remote = ghost.is_remote;
+        let escrow = Self::escrow_account(program_id, &ghost, accounts)?;
+        let escrow_recipient = if pending.target_state == GhostState::Settled {
+            config.treasury
+        } else {
+            ghost.initiator
+        };
+        let previous = ghost.state;
+        let (mint, token_escrow) = (ghost.source_token, ghost.token_escrow);
+        ghost.state = pending.target_state;
+        Self::write_ghost(accounts, ghost)?;
+
+        let escrowed = if is_remote {
+            0
+        } else {
+            let destination = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if escrow_recipient == Pubkey::default() || *destination.key != escrow_recipient {
+                msg!("Escrow destination mismatch");
+                return Err(GhostError::InvalidRecipient.into());
+            }
+            if token_escrow {
+                let tokens = Self::release_token_escrow(program_id, &ghost_id, &mint, escrow, accounts, &escrow_recipient)?;
+                msg!("{} tokens of {} released", tokens, mint);
+            }
+            Self::release_escrow(escrow, destination)?
+        };
+
+        Self::close_pending_force_settle(pending_account, &accounts[2])?;
+        msg!(
+            "Ghost force settled from {:?} to {:?}: {} lamports released",
+            previous,
+            pending.target_state,
+            escrowed
+        );
+        Ok(())
+    }
+
+    /// Accounts: config, admin, pending PDA. The record's rent goes to the admin.
+    fn cancel_force_settle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pending_account = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        Self::load_pending_force_settle(program_id, pending_account, &ghost_id)?;
+
+        Self::close_pending_force_settle(pending_account, admin)?;
+        msg!("Force settle cancelled");
+        Ok(())
+    }
+
+    fn load_pending_force_settle(
+        program_id: &Pubkey,
+        pending_account: &AccountInfo,
+        ghost_id: &[u8; 32],
+    ) -> Result<PendingForceSettle, ProgramError> {
+        let (expected, _) = Pubkey::find_program_address(&[b"force_settle", ghost_id], program_id);
+        if *pending_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if pending_account.owner != program_id || Self::is_blank(pending_account) {
+            msg!("No force settle pending");
+            return Err(GhostError::InvalidState.into());
+        }
+        Self::load_account(pending_account)
+    }
+
+    fn close_pending_force_settle(pending_account: &AccountInfo, to: &AccountInfo) -> ProgramResult {
+        let lamports = pending_account.lamports();
+        **pending_account.try_borrow_mut_lamports()? = 0;
+        **to.try_borrow_mut_lamports()? += lamports;
+        pending_account.data.borrow_mut().fill(0);
+        Ok(())
+    }
+
+    /// A transition may not record a time before the one it follows
+    fn ensure_monotonic(previous: i64, next: i64) -> ProgramResult {
+        if next < previous {
+            msg!("Timestamp {} precedes {}", next, previous);
+            return Err(GhostError::NonMonotonicTimestamp.into());
+        }
+        Ok(())
+    }
+
+    /// Account holding the ghost's source SOL: its vault PDA, found anywhere
+    /// after the validator account, or the ghost account itself for ghosts
+    /// created before vaults
+    fn escrow_account<'a, 'b>(
+        program_id: &Pubkey,
+        ghost: &GhostAccount,
+        accounts: &'b [AccountInfo<'a>],
+    ) -> Result<&'b AccountInfo<'a>, ProgramError> {
+        if !ghost.escrow_vault {
+            return accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys);
+        }
+        let (expected, _) = Pubkey::find_program_address(&[b"vault", &ghost.ghost_id], program_id);
+        accounts
+            .iter()
+            .skip(3)
+            .find(|account| *account.key == expected && account.owner == program_id)
+            .ok_or(ProgramError::NotEnoughAccountKeys)
+    }
+
+    /// Moves a token ghost's `amount` from the initiator's token account into
+    /// the escrow token account, which the vault PDA must own
+    fn escrow_tokens<'a>(
+        ghost: &GhostAccount,
+        initiator: &AccountInfo<'a>,
+        vault: &AccountInfo<'a>,
+        source: &AccountInfo<'a>,
+        escrow: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        if *token_program.key != SPL_TOKEN_PROGRAM_ID {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        let source_token = TokenAccount::load(source).ok_or(ProgramError::InvalidAccountData)?;
+        let escrow_token = TokenAccount::load(escrow).ok_or(ProgramError::InvalidAccountData)?;
+        if source_token.mint != ghost.source_token || escrow_token.mint != ghost.source_token {
+            msg!("Token account is not for mint {}", ghost.source_token);
+            return Err(GhostError::InvalidTokenAccount.into());
+        }
+        if escrow_token.owner != *vault.key {
+            msg!("Escrow token account is not owned by the vault");
+            return Err(GhostError::InvalidTokenAccount.into());
+        }
+        if source_token.amount < ghost.amount {
+            msg!("Token balance {} below {}", source_token.amount, ghost.amount);
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        let transfer_ix = token_transfer(source.key, escrow.key, initiator.key, ghost.amount);
+        solana_program::program::invoke(
+            &transfer_ix,
+            &[source.clone(), escrow.clone(), initiator.clone(), token_program.clone()],
+        )
+    }
+
+    /// Sends a token ghost's whole escrowed balance to `owner`'s token account
+    /// for the same mint, signed by the vault PDA. The escrow and destination
+    /// token accounts and the token program may be passed anywhere after the
+    /// validator account.
+    fn release_token_escrow<'a>(
+        program_id: &Pubkey,
+        ghost_id: &[u8; 32],
+        mint: &Pubkey,
+        vault: &AccountInfo<'a>,
+        accounts: &[AccountInfo<'a>],
+        owner: &Pubkey,
+    ) -> Result<u64, ProgramError> {
+        let (expected, bump) = Pubkey::find_program_address(&[b"vault", ghost_id], program_id);
+        if *vault.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let token_program = accounts
+            .iter()
+            .skip(3)
+            .find(|account| *account.key == SPL_TOKEN_PROGRAM_ID)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let held_by = |holder: &Pubkey| {
+            accounts.iter().skip(3).find_map(|account| {
+                let token = TokenAccount::load(account)?;
+                (token.mint == *mint && token.owner == *holder).then_some((account, token))
+            })
+        };
+        let (escrow, escrow_token) = held_by(vault.key).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let (destination, _) = held_by(owner).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if escrow_token.amount == 0 {
+            return Ok(0);
+        }
+
+        let transfer_ix = token_transfer(escrow.key, destination.key, vault.key, escrow_token.amount);
+        solana_program::program::invoke_signed(
+            &transfer_ix,
+            &[escrow.clone(), destination.clone(), vault.clone(), token_program.clone()],
+            &[&[b"vault", ghost_id, &[bump]]],
+        )?;
+        Ok(escrow_token.amount)
+    }
+
+    /// Sends everything the escrow holds above its rent-exempt minimum to `to`
+    fn release_escrow(escrow: &AccountInfo, to: &AccountInfo) -> Result<u64, ProgramError> {
+        let rent = Rent::get()?.minimum_balance(escrow.data_len());
+        let escrowed = escrow.lamports().saturating_sub(rent);
+        if escrowed > 0 {
+            **escrow.try_borrow_mut_lamports()? -= escrowed;
+            **to.try_borrow_mut_lamports()? += escrowed;
+        }
+        Ok(escrowed)
+    }
+
+    /// Counts distinct signatures from members of `validators` among `signers`
+    fn count_validator_signers(validators: &[Pubkey], signers: &[AccountInfo]) -> usize {
+        let mut seen: Vec<&Pubkey> = Vec::new();
+        for account in signers {
+            if account.is_signer && validators.contains(account.key) && !seen.contains(&account.key) {
+                seen.push(account.key);
+            }
+        }
+        seen.len()
+    }
+
+    fn ensure_threshold(config: &ProgramConfig, signers: &[AccountInfo]) -> ProgramResult {
+        Self::ensure_signers(&config.validators, config.required_signers(), signers)
+    }
+
+    fn ensure_signers(validators: &[Pubkey], required: usize, signers: &[AccountInfo]) -> ProgramResult {
+        let approvals = Self::count_validator_signers(validators, signers);
+        if approvals < required {
+            msg!("Validator threshold not met: {} < {}", approvals, required);
+            return Err(GhostError::ThresholdNotMet.into());
+        }
+        Ok(())
+    }
+
+    /// Records the live validator set on the ghost at burn time, creating the
+    /// snapshot PDA the first time this exact set is used
+    fn snapshot_validators<'a>(
+        program_id: &Pubkey,
+        config: &ProgramConfig,
+        ghost: &mut GhostAccount,
+        payer: &AccountInfo<'a>,
+        snapshot_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        let required = config.required_signers() as u8;
+        let set_hash = validator_set_hash(&config.validators, required);
+        let (expected, bump) = Pubkey::find_program_address(&[b"validators", &set_hash], program_id);
+        if *snapshot_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if snapshot_account.data_is_empty() {
+            Self::create_pda_account(
+                program_id,
+                payer,
+                snapshot_account,
+                system_program,
+                ValidatorSnapshot::space(config.validators.len()),
+                &[b"validators", &set_hash, &[bump]],
+            )?;
+            let snapshot = ValidatorSnapshot {
+                set_hash,
+                required_signers: required,
+                validators: config.validators.clone(),
+            };
+            Self::save_account(snapshot_account, &snapshot)?;
+        }
+        ghost.validator_set_hash = set_hash;
+        ghost.required_signers = required;
+        Ok(())
+    }
+
+    /// Validators allowed to attest for `ghost` and how many must sign: the set
+    /// snapshotted at burn time (passed as the fourth account), or the live set
+    /// with the ghost's recorded requirement for ghosts that carry no
+    /// snapshot, such as mirrored ghosts
+    fn attesting_set(
+        program_id: &Pubkey,
+        config: &ProgramConfig,
+        ghost: &GhostAccount,
+        accounts: &[AccountInfo],
+    ) -> Result<(Vec<Pubkey>, usize), ProgramError> {
+        if ghost.validator_set_hash == [0u8; 32] {
+            return Ok((config.validators.clone(), ghost.required_signers(config)));
+        }
+        let snapshot_account = accounts.get(3).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let (expected, _) =
+            Pubkey::find_program_address(&[b"validators", &ghost.validator_set_hash], program_id);
+        if *snapshot_account.key != expected || snapshot_account.owner != program_id {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let snapshot: ValidatorSnapshot = Self::load_account(snapshot_account)?;
+        if snapshot.set_hash != ghost.validator_set_hash {
+            return Err(GhostError::AccountDeserialization.into());
+        }
+        Ok((snapshot.validators, snapshot.required_signers as usize))
+    }
+
+    /// The primary validator (third account) plus co-signers must come from
+    /// the ghost's attesting set
+    fn ensure_attested(
+        program_id: &Pubkey,
+        config: &ProgramConfig,
+        ghost: &GhostAccount,
+        accounts: &[AccountInfo],
+        required: Option<usize>,
+    ) -> ProgramResult {
+        let (validators, threshold) = Self::attesting_set(program_id, config, ghost, accounts)?;
+        let validator = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !validators.contains(validator.key) {
+            return Err(GhostError::UnauthorizedValidator.into());
+        }
+        if !validator.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        Self::ensure_signers(&validators, required.unwrap_or(threshold), &accounts[2..])
+    }
+
+    fn load_with_validator(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> Result<(ProgramConfig, GhostAccount), ProgramError> {
+        let (config, ghost) = Self::load_slot_with_validator(program_id, accounts, ghost_id)?;
+        Ok((config, ghost.ok_or(GhostError::GhostNotInitialized)?))
+    }
+
+    /// Like load_with_validator, but a never-written ghost account, which a
+    /// mirror is about to fill, loads as None
+    fn load_slot_with_validator(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> Result<(ProgramConfig, Option<GhostAccount>), ProgramError> {
+        let (config, ghost) = Self::load_ghost_slot(program_id, accounts, ghost_id)?;
+        let validator = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        config.assert_validator(validator.key)?;
+        if !validator.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        Ok((config, ghost))
+    }
+
+    /// Loads config and ghost without judging the validator account, for steps
+    /// that authorize against the ghost's validator snapshot instead
+    fn load_ghost(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> Result<(ProgramConfig, GhostAccount), ProgramError> {
+        let (config, ghost) = Self::load_ghost_slot(program_id, accounts, ghost_id)?;
+        Ok((config, ghost.ok_or(GhostError::GhostNotInitialized)?))
+    }
+
+    /// Loads config and the ghost account, telling apart the ways that can
+    /// fail: a never-written account is None, data that doesn't decode is
+    /// AccountDeserialization (or AccountVersionMismatch), and only a valid
+    /// ghost with a different ID is GhostMismatch
+    fn load_ghost_slot(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ghost_id: [u8; 32],
+    ) -> Result<(ProgramConfig, Option<GhostAccount>), ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let ghost_account = next_account_info(account_info_iter)?;
+        let _validator = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, ghost_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        if ghost_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        // Anything but a never-written account must load cleanly so legacy
+        // layouts aren't mistaken for fresh accounts
+        if Self::is_blank(ghost_account) {
+            return Ok((config, None));
+        }
+        let ghost: GhostAccount = Self::load_or_legacy(ghost_account)?;
+        if ghost.ghost_id != ghost_id {
+            msg!("Account holds a different ghost");
+            return Err(GhostError::GhostMismatch.into());
+        }
+
+        Ok((config, Some(ghost)))
+    }
+
+    /// Saves the ghost and, when its state changed, moves it between the
+    /// config's per-state counters
+    fn write_ghost<'a>(accounts: &[AccountInfo<'a>], ghost: GhostAccount) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let ghost_account = next_account_info(account_info_iter)?;
+        let validator = next_account_info(account_info_iter)?;
+
+        let previous = Self::store_ghost(ghost_account, &ghost, validator, accounts)?;
+
+        if previous != ghost.state {
+            // The caller has already validated the config account
+            let mut config = Self::decode_config(&config_account.data.borrow())?;
+            config.ghost_counts.record_transition(previous, ghost.state);
+            Self::write_config_header(config_account, &config)?;
+        }
+        Ok(())
+    }
+
+    /// Saves `ghost` and logs it as a GhostEvent chained to what it replaced.
+    /// Returns the replaced ghost's state. An account too small for the
+    /// current layout, such as a legacy ghost, is grown first with `payer`
+    /// covering the rent, which needs the system program in `accounts`.
+    fn store_ghost<'a>(
+        ghost_account: &AccountInfo<'a>,
+        ghost: &GhostAccount,
+        payer: &AccountInfo<'a>,
+        accounts: &[AccountInfo<'a>],
+    ) -> Result<GhostState, ProgramError> {
+        let previous: Option<GhostAccount> = if Self::is_blank(ghost_account) {
+            None
+        } else {
+            Some(Self::load_or_legacy(ghost_account)?)
+        };
+        let len = Self::versioned_len(ghost)?;
+        if len > ghost_account.data_len() {
+            let Some(system_program) = accounts.iter().find(|a| *a.key == system_program::id()) else {
+                msg!("Ghost account must grow to {} bytes; pass the system program", len);
+                return Err(GhostError::InvalidSystemProgram.into());
+            };
+            Self::ensure_account_len(ghost_account, payer, system_program, len)?;
+        }
+        Self::save_account(ghost_account, ghost)?;
+
+        events::GhostEvent {
+            previous: events::state_hash(previous.as_ref()),
+            ghost: Some(ghost.clone()),
+        }
+        .emit();
+        Ok(previous.map_or(GhostState::None, |previous| previous.state))
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════════
+    // LIQUIDITY POOL FUNCTIONS
+    // ═══════════════════════════════════════════════════════════════════════════════
+
+    /// Reads a pool in either layout: zero-copy, or borsh for pools created
+    /// before the zero-copy layout and not yet migrated
+    fn load_pool(account: &AccountInfo) -> Result<LiquidityPool, ProgramError> {
+        let mut data = account.data.borrow_mut();
+        if account_version(&data) == Some(POOL_ZERO_COPY_VERSION) {
+            return Ok(LiquidityPool::from(&*pool_data_mut(&mut data)?));
+        }
+        drop(data);
+        Self::load_or_legacy(account)
+    }
+
+    /// Writes a pool back and logs it as a PoolSnapshot chained to what it
+    /// replaced. A borsh pool is upgraded to the zero-copy layout as it is
+    /// written, which needs the account to be LiquidityPool::space() long.
+    fn save_pool(account: &AccountInfo, pool: &LiquidityPool) -> ProgramResult {
+        let mut data = account.data.borrow_mut();
+        if account_version(&data) == Some(POOL_ZERO_COPY_VERSION) {
+            let stored = pool_data_mut(&mut data)?;
+            let previous = events::state_hash(Some(&LiquidityPool::from(&*stored)));
+            *stored = PoolData::from(pool);
+            events::PoolSnapshot { previous, pool: pool.clone() }.emit();
+            return Ok(());
+        }
+        drop(data);
+
+        let previous: LiquidityPool = Self::load_or_legacy(account)?;
+        events::PoolSnapshot {
+            previous: events::state_hash(Some(&previous)),
+            pool: pool.clone(),
+        }
+        .emit();
+        Self::init_pool_data(account, pool)
+    }
+
+    /// Applies `update` to a pool's fields in place through bytemuck, with
+    /// no borsh decode or full write-back, and logs the result as a
+    /// PoolSnapshot. Pools still in the borsh layout go through a copy.
+    fn update_pool<F>(account: &AccountInfo, update: F) -> ProgramResult
+    where
+        F: FnOnce(&mut PoolData) -> ProgramResult,
+    {
+        let mut data = account.data.borrow_mut();
+        if account_version(&data) == Some(POOL_ZERO_COPY_VERSION) {
+            let stored = pool_data_mut(&mut data)?;
+            let previous = events::state_hash(Some(&LiquidityPool::from(&*stored)));
+            update(stored)?;
+            events::PoolSnapshot { previous, pool: LiquidityPool::from(&*stored) }.emit();
+            return Ok(());
+        }
+        drop(data);
+
+        let mut pool = PoolData::from(&Self::load_or_legacy::<LiquidityPool>(account)?);
+        update(&mut pool)?;
+        Self::save_pool(account, &LiquidityPool::from(&pool))
+    }
+
+    /// Refuses to move funds when the pool's lamports no longer cover its
+    /// bookkeeping: available liquidity, accrued fees and rent
+    fn ensure_pool_backed(pool_account: &AccountInfo, pool: &LiquidityPool) -> ProgramResult {
+        let rent = Rent::get()?.minimum_balance(pool_account.data_len());
+        let required = (pool.available_liquidity as u128) + (pool.total_fees as u128) + (rent as u128);
+        if (pool_account.lamports() as u128) < required {
+            msg!("Pool balance {} below required {}", pool_account.lamports(), required);
+            return Err(GhostError::BalanceMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Stamps the zero-copy header and writes the pool in place
+    fn init_pool_data(account: &AccountInfo, pool: &LiquidityPool) -> ProgramResult {
+        let mut data = account.data.borrow_mut();
+        if data.len() < LiquidityPool::space() {
+            msg!("Pool account too small for the zero-copy layout; grow it with MigrateAccount");
+            return Err(GhostError::AccountSerialization.into());
+        }
+        write_account_header(&mut data, POOL_ZERO_COPY_VERSION)?;
+        *pool_data_mut(&mut data)? = PoolData::from(pool);
+        Ok(())
+    }
+
+    /// Converts a borsh pool (versioned or legacy) to the zero-copy layout
+    fn migrate_pool<'a>(
+        account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        if account_version(&account.data.borrow()) == Some(POOL_ZERO_COPY_VERSION) {
+            msg!("Pool already uses the zero-copy layout");
+            return Err(GhostError::InvalidState.into());
+        }
+        let pool: LiquidityPool = Self::load_or_legacy(account)?;
+        Self::ensure_account_len(account, payer, system_program, LiquidityPool::space())?;
+        account.data.borrow_mut().fill(0);
+        Self::init_pool_data(account, &pool)
+    }
+
+    /// Initialize a new liquidity pool
+    fn initialize_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        pool_seed: [u8; 32],
+        dest_token: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let pool_account = next_account_info(account_info_iter)?;
+        let authority = next_account_info(account_info_iter)?;
+        let registry_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[pool_account, registry_account])?;
+
+        if !authority.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        // One pool per token: the registry PDA must not exist yet
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"pool_registry", dest_token.as_ref()], program_id);
+        if *registry_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if !registry_account.data_is_empty() {
+            msg!("Token already has a registered pool");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        Self::create_pda_account(
+            program_id,
+            authority,
+            registry_account,
+            system_program,
+            PoolRegistry::space(),
+            &[b"pool_registry", dest_token.as_ref(), &[bump]],
+        )?;
+        let registry = PoolRegistry {
+            dest_token,
+            pool_seed,
+            pool: *pool_account.key,
+        };
+        Self::save_account(registry_account, &registry)?;
+
+        let pool = LiquidityPool {
+            seed: pool_seed,
+            total_deposited: 0,
+            total_shares: 0,
+            total_fees: 0,
+            available_liquidity: 0,
+            active: true,
+            lifetime_volume: 0,
+            payment_count: 0,
+            closed: false,
+            min_deposit: 0,
+            max_pool_size: 0,
+            withdraw_grace_period: 0,
+            min_withdrawal: 0,
+            breaker_window_secs: 0,
+            breaker_max_volume: 0,
+            breaker_window_start: 0,
+            breaker_volume: 0,
+            breaker_tripped: false,
+            reserved_buffer: 0,
+        };
+
+        Self::init_pool_data(pool_account, &pool)?;
+        events::PoolSnapshot {
+            previous: events::state_hash::<LiquidityPool>(None),
+            pool,
+        }
+        .emit();
+
+        msg!("Liquidity pool initialized for token {}", dest_token);
+        Ok(())
+    }
+
+    fn set_pool_active(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        active: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, pool_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        Self::update_pool(pool_account, |pool| {
+            if pool.closed != 0 {
+                msg!("Pool is closed");
+                return Err(GhostError::InvalidState.into());
+            }
+            pool.active = active as u8;
+            Ok(())
+        })?;
+
+        msg!("Pool active: {}", active);
+        Ok(())
+    }
+
+    fn set_pool_limits(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        min_deposit: u64,
+        max_pool_size: u64,
+        min_withdrawal: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, pool_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        if max_pool_size != 0 && min_deposit > max_pool_size {
+            return Err(GhostError::InvalidConfig.into());
+        }
+
+        Self::update_pool(pool_account, |pool| {
+            pool.min_deposit = min_deposit;
+            pool.max_pool_size = max_pool_size;
+            pool.min_withdrawal = min_withdrawal;
+            Ok(())
+        })?;
+
+        msg!(
+            "Pool limits: min deposit {}, max size {}, min withdrawal {}",
+            min_deposit, max_pool_size, min_withdrawal
+        );
+        Ok(())
+    }
+
+    fn set_withdraw_grace_period(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        grace_period: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, pool_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        if grace_period < 0 {
+            return Err(GhostError::InvalidConfig.into());
+        }
+
+        Self::update_pool(pool_account, |pool| {
+            pool.withdraw_grace_period = grace_period;
+            Ok(())
+        })?;
+
+        msg!("Withdraw grace period set to {}s", grace_period);
+        Ok(())
+    }
+
+    fn set_breaker(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        window_secs: i64,
+        max_volume: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, pool_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        if window_secs < 0 {
+            return Err(GhostError::InvalidConfig.into());
+        }
+
+        Self::update_pool(pool_account, |pool| {
+            pool.breaker_window_secs = window_secs;
+            pool.breaker_max_volume = max_volume;
+            Ok(())
+        })?;
+
+        msg!("Circuit breaker: {} lamports per {}s", max_volume, window_secs);
+        Ok(())
+    }
+
+    fn reset_breaker(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, pool_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        Self::update_pool(pool_account, |pool| {
+            pool.breaker_tripped = 0;
+            pool.breaker_window_start = now;
+            pool.breaker_volume = 0;
+            Ok(())
+        })?;
+
+        msg!("Circuit breaker reset");
+        Ok(())
+    }
+
+    /// Accounts: config, admin, pool
+    fn set_reserved_buffer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reserved_buffer: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, pool_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        Self::update_pool(pool_account, |pool| {
+            pool.reserved_buffer = reserved_buffer;
+            Ok(())
+        })?;
+
+        msg!("Reserved buffer set to {} lamports", reserved_buffer);
+        Ok(())
+    }
+
+    /// Wind down an inactive pool once every LP has withdrawn.
+    /// Accounts: config, admin, pool, destination.
+    fn drain_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        destination: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        let destination_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, pool_account, destination_account])?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if pool_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        if *destination_account.key != destination {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut pool = Self::load_pool(pool_account)?;
+        if pool.active || pool.closed {
+            return Err(GhostError::InvalidState.into());
+        }
+        // Only the shares locked by the first deposit may remain: no position
+        // owns them, so nothing is left to redeem
+        if pool.total_shares != 0 && pool.total_shares != MINIMUM_LIQUIDITY {
+            msg!("{} LP shares still outstanding", pool.total_shares.saturating_sub(MINIMUM_LIQUIDITY));
+            return Err(GhostError::InvalidState.into());
+        }
+
+        let rent = Rent::get()?.minimum_balance(pool_account.data_len());
+        let residual = pool_account.lamports().saturating_sub(rent);
+        if residual > 0 {
+            **pool_account.try_borrow_mut_lamports()? -= residual;
+            **destination_account.try_borrow_mut_lamports()? += residual;
+        }
+
+        pool.total_deposited = 0;
+        pool.total_shares = 0;
+        pool.total_fees = 0;
+        pool.available_liquidity = 0;
+        pool.closed = true;
+        Self::save_pool(pool_account, &pool)?;
+
+        msg!("Pool drained: {} lamports to {}", residual, destination);
+        Ok(())
+    }
+
+    /// Deposit SOL into the pool
+    fn deposit_to_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        min_shares_out: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let pool_account = next_account_info(account_info_iter)?;
+        let lp_position_account = next_account_info(account_info_iter)?;
+        let depositor = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[pool_account, lp_position_account])?;
+
+        if !depositor.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if pool_account.owner != program_id || lp_position_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        // The deposit transfer is a CPI through this account
+        if system_program.key != &system_program::id() {
+            return Err(GhostError::InvalidSystemProgram.into());
+        }
+
+        // Load pool
+        let mut pool = Self::load_pool(pool_account)?;
+
+        let mut position: LPPosition = if Self::is_blank(lp_position_account) {
+            LPPosition {
+                owner: *depositor.key,
+                pool: pool.seed,
+                shares: 0,
+                deposited_at: 0,
+            }
+        } else {
+            Self::load_or_legacy(lp_position_account)?
+        };
+        if position.owner != *depositor.key {
+            msg!("Not position owner");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // Shares are only worth anything in the pool that minted them
+        if position.pool != pool.seed {
+            msg!("Position belongs to a different pool");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if !pool.active {
+            msg!("Pool not active");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if amount < pool.min_deposit {
+            msg!("Deposit below minimum: {} < {}", amount, pool.min_deposit);
+            return Err(GhostError::BelowMinimumDeposit.into());
+        }
+        if pool.max_pool_size != 0
+            && pool.total_deposited.saturating_add(amount) > pool.max_pool_size
+        {
+            msg!("Deposit would exceed pool cap of {}", pool.max_pool_size);
+            return Err(GhostError::PoolCapExceeded.into());
+        }
+
+        // Credit donated lamports to existing LPs before pricing the deposit
+        let rent = Rent::get()?.minimum_balance(pool_account.data_len());
+        let surplus = pool.absorb_surplus(pool_account.lamports(), rent);
+        if surplus > 0 {
+            msg!("Absorbed {} unaccounted lamports into the pool", surplus);
+        }
+
+        // Calculate shares (rounded down, in the pool's favor)
+        let shares = pool.shares_for_deposit(amount).ok_or_else(|| {
+            msg!("Deposit of {} lamports mints no shares", amount);
+            ProgramError::InvalidArgument
+        })?;
+        // The price can move between quoting and landing, by payouts, fees
+        // or a donation to the pool
+        if shares < min_shares_out {
+            msg!("Deposit mints {} shares, below the minimum {}", shares, min_shares_out);
+            return Err(GhostError::SlippageExceeded.into());
+        }
+        let locked = pool.locked_shares();
+
+        // Transfer SOL from depositor to pool
+        let transfer_ix = solana_program::system_instruction::transfer(
+            depositor.key,
+            pool_account.key,
+            amount,
+        );
+        solana_program::program::invoke(
+            &transfer_ix,
+            &[depositor.clone(), pool_account.clone(), system_program.clone()],
+        )?;
+
+        // Update pool
+        pool.total_deposited = pool.total_deposited.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        pool.total_shares += shares + locked;
+        pool.available_liquidity = pool.available_liquidity.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+
+        Self::save_pool(pool_account, &pool)?;
+        events::PoolState::from_pool(&pool).emit();
+
+        // Update LP position
+
+        position.shares += shares;
+        position.deposited_at = Clock::get()?.unix_timestamp;
+
+        Self::save_account(lp_position_account, &position)?;
+
+        msg!("Deposited {} lamports, received {} shares", amount, shares);
+        Ok(())
+    }
+
+    /// Withdraw SOL from the pool
+    fn withdraw_from_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        shares: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let pool_account = next_account_info(account_info_iter)?;
+        let lp_position_account = next_account_info(account_info_iter)?;
+        let withdrawer = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[pool_account, lp_position_account])?;
+
+        if !withdrawer.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if pool_account.owner != program_id || lp_position_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        if shares == 0 {
+            return Err(GhostError::ZeroShares.into());
+        }
+
+        // Load pool
+        let mut pool = Self::load_pool(pool_account)?;
+
+        // Load position
+        let mut position: LPPosition = Self::load_or_legacy(lp_position_account)?;
+
+        if position.owner != *withdrawer.key {
+            msg!("Not position owner");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // Shares are only worth anything in the pool that minted them
+        if position.pool != pool.seed {
+            msg!("Position belongs to a different pool");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if position.shares < shares {
+            msg!("Insufficient shares");
+            return Err(ProgramError::InsufficientFunds);
+        }
+        // Hard lock so transient capital can't skim fees around a payout
+        let now = Clock::get()?.unix_timestamp;
+        if now - position.deposited_at < pool.withdraw_grace_period {
+            msg!("Withdrawal locked until {}", position.deposited_at + pool.withdraw_grace_period);
+            return Err(GhostError::WithdrawLocked.into());
+        }
+
+        // Fail closed: a pool whose books don't add up must not pay some LPs
+        // and strand the rest
+        let rent_exempt = Rent::get()?.minimum_balance(pool_account.data_len());
+        if let Err(err) = pool.check_invariants(pool_account.lamports(), rent_exempt) {
+            msg!("Pool invariant violated: {:?}", err);
+            return Err(GhostError::BalanceMismatch.into());
+        }
+
+        // Calculate withdrawal amount (includes earned fees)
+        let amount = pool.shares_value(shares);
+        if amount == 0 {
+            msg!("{} shares are worth nothing after rounding", shares);
+            return Err(GhostError::ZeroShares.into());
+        }
+        // Closing out a whole position is always allowed, however small
+        if amount < pool.min_withdrawal && shares != position.shares {
+            msg!("Withdrawal below minimum: {} < {}", amount, pool.min_withdrawal);
+            return Err(GhostError::BelowMinimumWithdrawal.into());
+        }
+
+        if pool.available_liquidity < amount {
+            msg!("Insufficient pool liquidity");
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // Transfer SOL from pool to withdrawer
+        **pool_account.try_borrow_mut_lamports()? -= amount;
+        **withdrawer.try_borrow_mut_lamports()? += amount;
+
+        // Update pool
+        pool.total_deposited -= amount;
+        pool.total_shares -= shares;
+        pool.available_liquidity -= amount;
+
+        Self::save_pool(pool_account, &pool)?;
+        events::PoolState::from_pool(&pool).emit();
+
+        // Update position
+        position.shares -= shares;
+
+        Self::save_account(lp_position_account, &position)?;
+
+        msg!("Withdrew {} lamports for {} shares", amount, shares);
+        Ok(())
+    }
+
+    /// Accounts: target position, source position, owner. The source's shares
+    /// move into the target and its rent goes back to the owner.
+    fn merge_positions(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let target_account = next_account_info(account_info_iter)?;
+        let source_account = next_account_info(account_info_iter)?;
+        let owner = next_account_info(account_info_iter)?;
+
+        if !owner.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if target_account.owner != program_id || source_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+        Self::ensure_distinct(&[target_account, source_account])?;
+
+        let mut target: LPPosition = Self::load_or_legacy(target_account)?;
+        let source: LPPosition = Self::load_or_legacy(source_account)?;
+        if target.owner != *owner.key || source.owner != *owner.key {
+            msg!("Not position owner");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if target.pool != source.pool {
+            msg!("Positions belong to different pools");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        target.shares = target
+            .shares
+            .checked_add(source.shares)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        // Keep the later deposit so merging can't shorten the withdraw grace period
+        target.deposited_at = target.deposited_at.max(source.deposited_at);
+        Self::save_account(target_account, &target)?;
+
+        let lamports = source_account.lamports();
+        **source_account.try_borrow_mut_lamports()? = 0;
+        **owner.try_borrow_mut_lamports()? += lamports;
+        source_account.data.borrow_mut().fill(0);
+
+        msg!("Merged {} shares, {} lamports reclaimed", source.shares, lamports);
+        Ok(())
+    }
+
+    /// Execute a cross-chain payment (sends SOL from pool to recipient).
+    /// Accounts: config, pool, recipient, relayer, intent, pool registry PDA,
+    /// chain registry PDA of the sender chain (registered or not), then as
+    /// needed: relayer stats PDA and system program, relayer bond PDA, and the
+    /// whitelist PDA of a program-owned recipient.
+    fn execute_payment(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        intent_id: [u8; 32],
+        recipient: Pubkey,
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        let recipient_account = next_account_info(account_info_iter)?;
+        let relayer = next_account_info(account_info_iter)?;
+        let intent_account = next_account_info(account_info_iter)?;
+        let registry_account = next_account_info(account_info_iter)?;
+        let chain_account = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[
+            config_account,
+            pool_account,
+            recipient_account,
+            intent_account,
+            registry_account,
+            chain_account,
+        ])?;
+
+        // Verify relayer is authorized
+        let mut config = Self::load_config(program_id, config_account)?;
+        config.ensure_not_paused()?;
+        config.assert_validator(relayer.key)?;
+
+        if !relayer.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if pool_account.owner != program_id || intent_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
+
+        // The pool must be the one registered for the intent's token
+        let mut intent: PaymentIntent = Self::load_or_legacy(intent_account)?;
+        if intent.intent_id != intent_id {
+            msg!("Intent mismatch");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if intent.executed || intent.cancelled {
+            return Err(GhostError::InvalidState.into());
+        }
+        // Partial payouts all go to the recipient of the first one
+        if intent.amount_paid > 0 && intent.recipient != recipient {
+            msg!("Recipient differs from earlier partial payout");
+            return Err(GhostError::RecipientMismatch.into());
+        }
+        let amount_paid = intent.amount_paid.saturating_add(amount);
+        if amount_paid > intent.amount {
+            msg!("Overpayment: {} > {}", amount_paid, intent.amount);
+            return Err(GhostError::Overpayment.into());
+        }
+        let registry = Self::resolve_pool(program_id, registry_account, &intent.dest_token)?;
+        if registry.pool != *pool_account.key {
+            msg!("Pool does not back token {}", intent.dest_token);
+            return Err(GhostError::PoolTokenMismatch.into());
+        }
+        if *recipient_account.key != recipient {
+            msg!("Recipient mismatch");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if recipient_account.executable {
+            return Err(GhostError::InvalidRecipient.into());
+        }
+
+        // Load pool
+        let mut pool = Self::load_pool(pool_account)?;
+        if pool.seed != registry.pool_seed {
+            msg!("Pool seed does not match the registry for token {}", intent.dest_token);
+            return Err(GhostError::PoolTokenMismatch.into());
+        }
+
+        if !pool.active {
+            msg!("Pool not active");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if pool.payable_liquidity() < amount {
+            msg!(
+                "Insufficient pool liquidity: {} < {} ({} reserved)",
+                pool.payable_liquidity(),
+                amount,
+                pool.reserved_buffer
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // Per-relayer throttle: accounts continue with relayer stats PDA, system program
+        if config.relayer_window_cap > 0 {
+            let stats_account = next_account_info(account_info_iter)?;
+            let system_program = next_account_info(account_info_iter)?;
+            Self::record_relayer_payout(program_id, &config, relayer, stats_account, system_program, amount)?;
+        }
+        // Bonded relayers only: accounts continue with the relayer bond PDA
+        if config.relayer_min_bond > 0 {
+            Self::ensure_bonded(program_id, &config, relayer, next_account_info(account_info_iter)?)?;
+        }
+        // Native SOL payouts go to plain wallets, or into accounts of a
+        // whitelisted program: accounts continue with its whitelist PDA
+        if recipient_account.owner != &system_program::id() {
+            let whitelist_account = next_account_info(account_info_iter)?;
+            Self::ensure_recipient_program(program_id, recipient_account.owner, whitelist_account)?;
+        }
+
+        Self::ensure_pool_backed(pool_account, &pool)?;
+
+        pool.record_breaker_payout(Clock::get()?.unix_timestamp, amount)?;
+
+        // Transfer SOL from pool to recipient; the fee stays in the pool and
+        // is credited to its LPs
+        let chain = Self::find_chain(program_id, chain_account, intent.sender_chain)?;
+        let fee = config.route_fee(chain.as_ref(), amount);
+        **pool_account.try_borrow_mut_lamports()? -= amount - fee;
+        **recipient_account.try_borrow_mut_lamports()? += amount - fee;
+
+        // Update pool
+        pool.available_liquidity -= amount;
+        pool.credit_fees(fee).ok_or(ProgramError::ArithmeticOverflow)?;
+        pool.lifetime_volume += amount as u128;
+        pool.payment_count += 1;
+
+        Self::save_pool(pool_account, &pool)?;
+        events::PoolState::from_pool(&pool).emit();
+        if pool.breaker_tripped {
+            msg!("Circuit breaker tripped: {} lamports in window", pool.breaker_volume);
+        }
+
+        intent.recipient = recipient;
+        intent.amount_paid = amount_paid;
+        intent.executed = amount_paid == intent.amount;
+        if intent.executed && Self::uncount_intent(&mut config, &mut intent)? {
+            Self::write_config_header(config_account, &config)?;
         }
+        Self::save_account(intent_account, &intent)?;
+
+        msg!("Payment executed: {} lamports to {} (intent: {:?}, {}/{})", 
+            amount, recipient, &intent_id[..8], amount_paid, intent.amount);
+        Ok(())
     }
 
-    fn initialize(
+    fn check_invariants(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        admin: Pubkey,
-        validator_threshold: u8,
-        max_validators: u8,
+        pool_seed: [u8; 32],
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let config_account = next_account_info(account_info_iter)?;
-        let signer = next_account_info(account_info_iter)?;
-        
-        if !signer.is_signer {
-            return Err(GhostError::MissingSigner.into());
-        }
-        if config_account.owner != program_id {
+        let pool_account = next_account_info(account_info_iter)?;
+
+        if pool_account.owner != program_id {
             return Err(GhostError::IncorrectProgramId.into());
         }
+        let pool = Self::load_pool(pool_account)?;
+        if pool.seed != pool_seed {
+            msg!("Pool seed mismatch");
+            return Err(ProgramError::InvalidAccountData);
+        }
 
-        let config = ProgramConfig {
-            admin,
-            validator_threshold,
-            max_validators,
-            validators: vec![],
-        };
+        let rent_exempt = Rent::get()?.minimum_balance(pool_account.data_len());
+        if let Err(err) = pool.check_invariants(pool_account.lamports(), rent_exempt) {
+            msg!("Pool invariant violated: {:?}", err);
+            return Err(err.into());
+        }
+        Ok(())
+    }
 
-        config
-            .serialize(&mut &mut config_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+    fn set_chain(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        chain_id: u64,
+        enabled: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let chain_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
 
-        msg!("Ghost program initialized");
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if chain_id == config.local_chain() {
+            return Err(GhostError::InvalidRoute.into());
+        }
+
+        let chain_seed = chain_id.to_le_bytes();
+        let (expected, bump) = Pubkey::find_program_address(&[b"chain", &chain_seed], program_id);
+        if *chain_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if chain_account.data_is_empty() {
+            Self::create_pda_account(
+                program_id,
+                admin,
+                chain_account,
+                system_program,
+                ChainConfig::space(),
+                &[b"chain", &chain_seed, &[bump]],
+            )?;
+        }
+
+        let mut chain: ChainConfig = if Self::is_blank(chain_account) {
+            ChainConfig { chain_id, ..ChainConfig::default() }
+        } else {
+            Self::load_account(chain_account)?
+        };
+        chain.enabled = enabled;
+        Self::save_account(chain_account, &chain)?;
+        msg!("Chain {} enabled: {}", chain_id, enabled);
         Ok(())
     }
 
-    fn load_config(
+    /// Accounts: config, admin, chain registry PDA (must already exist),
+    /// system program (only to grow an entry registered before the delay)
+    fn set_chain_finality(
         program_id: &Pubkey,
-        account: &AccountInfo,
-    ) -> Result<ProgramConfig, ProgramError> {
-        if account.owner != program_id {
-            return Err(GhostError::IncorrectProgramId.into());
+        accounts: &[AccountInfo],
+        chain_id: u64,
+        finality_delay: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let chain_account = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if finality_delay < 0 {
+            return Err(GhostError::InvalidConfig.into());
         }
-        // Use deserialize with a reader to handle accounts with extra space
-        let data = account.data.borrow();
-        let mut slice: &[u8] = &data;
-        ProgramConfig::deserialize(&mut slice).map_err(|e| {
-            msg!("Failed to deserialize config: {:?}", e);
-            GhostError::AccountDeserialization.into()
-        })
-    }
 
-    fn save_config(account: &AccountInfo, config: &ProgramConfig) -> ProgramResult {
-        config
-            .serialize(&mut &mut account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        let mut chain = Self::load_chain(program_id, chain_account, chain_id)?;
+        chain.finality_delay = finality_delay;
+        // Entries registered before the delay existed are too short for it;
+        // only those need the system program, so older callers still work
+        if chain_account.data_len() < ChainConfig::space() {
+            let system_program = next_account_info(account_info_iter)?;
+            Self::ensure_account_len(chain_account, admin, system_program, ChainConfig::space())?;
+        }
+        Self::save_account(chain_account, &chain)?;
+
+        msg!("Chain {} finality delay set to {}s", chain_id, finality_delay);
         Ok(())
     }
 
-    fn ensure_admin(config: &ProgramConfig, signer: &AccountInfo) -> ProgramResult {
-        if !signer.is_signer || signer.key != &config.admin {
-            return Err(GhostError::UnauthorizedAdmin.into());
+    /// Accounts: config, admin, chain registry PDA (must already exist),
+    /// system program
+    fn set_chain_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        chain_id: u64,
+        fee_bps: Option<u16>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let chain_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if fee_bps.is_some_and(|bps| bps as u64 > BPS_DENOMINATOR) {
+            return Err(GhostError::InvalidConfig.into());
         }
+
+        let mut chain = Self::load_chain(program_id, chain_account, chain_id)?;
+        chain.fee_bps = fee_bps;
+        // Entries registered before the override existed are too short for it
+        Self::ensure_account_len(chain_account, admin, system_program, ChainConfig::space())?;
+        Self::save_account(chain_account, &chain)?;
+
+        msg!("Chain {} fee override: {:?} bps", chain_id, fee_bps);
         Ok(())
     }
 
-    fn set_validator(
+    /// Accounts: config, admin, chain registry PDA (must already exist),
+    /// system program
+    fn set_chain_address_family(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        validator: Pubkey,
-        enabled: bool,
+        chain_id: u64,
+        family: ChainFamily,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let config_account = next_account_info(account_info_iter)?;
         let admin = next_account_info(account_info_iter)?;
+        let chain_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
 
-        let mut config = Self::load_config(program_id, config_account)?;
-        Self::ensure_admin(&config, admin)?;
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
 
-        if enabled {
-            if !config.validators.iter().any(|v| v == &validator) {
-                if config.validators.len() >= config.max_validators as usize {
-                    return Err(GhostError::ValidatorLimit.into());
-                }
-                config.validators.push(validator);
-            }
-        } else {
-            config.validators.retain(|v| v != &validator);
-        }
+        let mut chain = Self::load_chain(program_id, chain_account, chain_id)?;
+        chain.address_family = Some(family);
+        // Entries registered before families existed are too short for one
+        Self::ensure_account_len(chain_account, admin, system_program, ChainConfig::space())?;
+        Self::save_account(chain_account, &chain)?;
 
-        Self::save_config(config_account, &config)?;
-        msg!("Validator updated");
+        msg!("Chain {} address family: {:?}", chain_id, family);
         Ok(())
     }
 
-    fn create_ghost(
+    /// The registry entry for `chain_id` if one has been registered
+    fn find_chain(
+        program_id: &Pubkey,
+        chain_account: &AccountInfo,
+        chain_id: u64,
+    ) -> Result<Option<ChainConfig>, ProgramError> {
+        let (expected, _) =
+            Pubkey::find_program_address(&[b"chain", &chain_id.to_le_bytes()], program_id);
+        if *chain_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if chain_account.owner != program_id || chain_account.data_is_empty() {
+            return Ok(None);
+        }
+        Self::load_account(chain_account).map(Some)
+    }
+
+    /// Loads the registry entry for `chain_id`, enabled or not
+    fn load_chain(
+        program_id: &Pubkey,
+        chain_account: &AccountInfo,
+        chain_id: u64,
+    ) -> Result<ChainConfig, ProgramError> {
+        let (expected, _) =
+            Pubkey::find_program_address(&[b"chain", &chain_id.to_le_bytes()], program_id);
+        if *chain_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if chain_account.owner != program_id || chain_account.data_is_empty() {
+            msg!("Chain {} is not registered", chain_id);
+            return Err(GhostError::ChainDisabled.into());
+        }
+        Self::load_account(chain_account)
+    }
+
+    /// Loads the registry entry for `chain_id`, failing unless it is enabled
+    fn ensure_chain_enabled(
+        program_id: &Pubkey,
+        chain_account: &AccountInfo,
+        chain_id: u64,
+    ) -> Result<ChainConfig, ProgramError> {
+        let chain = Self::load_chain(program_id, chain_account, chain_id)?;
+        if !chain.enabled {
+            msg!("Chain {} is disabled", chain_id);
+            return Err(GhostError::ChainDisabled.into());
+        }
+        Ok(chain)
+    }
+
+    /// Accounts: config, admin, token metadata PDA, system program
+    fn set_token_metadata(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        ghost_id: [u8; 32],
-        amount: u64,
-        destination_chain: u64,
-        destination_address: [u8; 64],
-        source_token: Pubkey,
-        destination_token: Pubkey,
+        mint: Pubkey,
+        decimals: u8,
+        symbol: String,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let config_account = next_account_info(account_info_iter)?;
-        let ghost_account = next_account_info(account_info_iter)?;
-        let payer = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let metadata_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
 
-        let _config = Self::load_config(program_id, config_account)?;
-        if !payer.is_signer {
-            return Err(GhostError::MissingSigner.into());
-        }
-        if ghost_account.owner != program_id {
-            return Err(GhostError::IncorrectProgramId.into());
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if symbol.is_empty() || symbol.len() > TOKEN_SYMBOL_LEN || symbol.contains('\0') {
+            msg!("Symbol must be 1 to {} bytes", TOKEN_SYMBOL_LEN);
+            return Err(GhostError::InvalidConfig.into());
         }
 
-        let ghost = GhostAccount {
-            ghost_id,
-            initiator: *payer.key,
-            source_token,
-            destination_token,
-            destination_chain,
-            destination_address,
-            state: GhostState::Created,
-            amount,
-            lock_ts: Clock::get()?.unix_timestamp,
-            burn_ts: 0,
-            mint_ts: 0,
-            burn_proof: [0u8; 32],
-            mint_proof: [0u8; 32],
-            is_remote: false,
-            remote_ack: false,
-        };
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"token_metadata", mint.as_ref()], program_id);
+        if *metadata_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if metadata_account.data_is_empty() {
+            Self::create_pda_account(
+                program_id,
+                admin,
+                metadata_account,
+                system_program,
+                TokenMetadata::space(),
+                &[b"token_metadata", mint.as_ref(), &[bump]],
+            )?;
+        }
 
-        ghost
-            .serialize(&mut &mut ghost_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        let mut padded = [0u8; TOKEN_SYMBOL_LEN];
+        padded[..symbol.len()].copy_from_slice(symbol.as_bytes());
+        let metadata = TokenMetadata { mint, decimals, symbol: padded };
+        Self::save_account(metadata_account, &metadata)?;
 
-        msg!("Ghost created");
+        msg!("Token {} metadata: {} ({} decimals)", mint, symbol, decimals);
         Ok(())
     }
 
-    fn lock_ghost(program_id: &Pubkey, accounts: &[AccountInfo], ghost_id: [u8; 32]) -> ProgramResult {
-        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
-        if ghost.state != GhostState::Created {
-            return Err(GhostError::InvalidState.into());
+    /// Loads the registry entry for `dest_token`, failing if no pool backs it
+    fn resolve_pool(
+        program_id: &Pubkey,
+        registry_account: &AccountInfo,
+        dest_token: &Pubkey,
+    ) -> Result<PoolRegistry, ProgramError> {
+        let (expected, _) =
+            Pubkey::find_program_address(&[b"pool_registry", dest_token.as_ref()], program_id);
+        if *registry_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
         }
-        ghost.state = GhostState::Locked;
-        ghost.lock_ts = Clock::get()?.unix_timestamp;
-        Self::write_ghost(accounts, ghost)?;
-        let _ = config;
-        msg!("Ghost locked");
-        Ok(())
+        if registry_account.owner != program_id || registry_account.data_is_empty() {
+            msg!("No pool registered for token {}", dest_token);
+            return Err(GhostError::PoolNotRegistered.into());
+        }
+        Self::load_account(registry_account)
     }
 
-    fn burn_ghost(
+    /// Accounts: config, admin, whitelist PDA, system program
+    fn set_recipient_program(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        ghost_id: [u8; 32],
-        burn_proof: [u8; 32],
+        program: Pubkey,
+        allowed: bool,
     ) -> ProgramResult {
-        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
-        if ghost.state != GhostState::Locked {
-            return Err(GhostError::InvalidState.into());
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let whitelist_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        // Paying into this program's own accounts would corrupt pool state
+        if program == *program_id || program == system_program::id() {
+            return Err(GhostError::InvalidConfig.into());
         }
-        ghost.state = GhostState::Burned;
-        ghost.burn_ts = Clock::get()?.unix_timestamp;
-        ghost.burn_proof = burn_proof;
-        Self::write_ghost(accounts, ghost)?;
-        let _ = config;
-        msg!("Ghost burned");
+
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"recipient_program", program.as_ref()], program_id);
+        if *whitelist_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if whitelist_account.data_is_empty() {
+            Self::create_pda_account(
+                program_id,
+                admin,
+                whitelist_account,
+                system_program,
+                RecipientProgram::space(),
+                &[b"recipient_program", program.as_ref(), &[bump]],
+            )?;
+        }
+
+        let entry = RecipientProgram { program, allowed };
+        Self::save_account(whitelist_account, &entry)?;
+        msg!("Recipient program {} allowed: {}", program, allowed);
         Ok(())
     }
 
-    fn mirror_ghost(
+    /// Fails unless `owner` has an allowed entry in the payout whitelist
+    fn ensure_recipient_program(
         program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        ghost_id: [u8; 32],
-        source_chain: u64,
-        amount: u64,
-        burn_proof: [u8; 32],
-        source_token: Pubkey,
-        destination_token: Pubkey,
+        owner: &Pubkey,
+        whitelist_account: &AccountInfo,
     ) -> ProgramResult {
-        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
-        if ghost.state != GhostState::None && !ghost.is_remote {
-            return Err(GhostError::GhostExists.into());
+        let (expected, _) =
+            Pubkey::find_program_address(&[b"recipient_program", owner.as_ref()], program_id);
+        if *whitelist_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let allowed = whitelist_account.owner == program_id
+            && !Self::is_blank(whitelist_account)
+            && Self::load_account::<RecipientProgram>(whitelist_account)?.allowed;
+        if !allowed || owner == program_id {
+            msg!("Recipient owner {} is not whitelisted", owner);
+            return Err(GhostError::InvalidRecipient.into());
         }
-
-        ghost.ghost_id = ghost_id;
-        ghost.initiator = Pubkey::default();
-        ghost.source_token = source_token;
-        ghost.destination_token = destination_token;
-        ghost.destination_chain = source_chain;
-        ghost.state = GhostState::Burned;
-        ghost.amount = amount;
-        ghost.burn_ts = Clock::get()?.unix_timestamp;
-        ghost.burn_proof = burn_proof;
-        ghost.is_remote = true;
-
-        Self::write_ghost(accounts, ghost)?;
-        let _ = config;
-        msg!("Ghost mirrored from remote chain");
         Ok(())
     }
 
-    fn mint_ghost(
+    /// Charges `amount` against the relayer's current window, creating its
+    /// stats PDA on first use
+    fn record_relayer_payout<'a>(
         program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        ghost_id: [u8; 32],
-        mint_proof: [u8; 32],
-        recipient: Pubkey,
+        config: &ProgramConfig,
+        relayer: &AccountInfo<'a>,
+        stats_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        amount: u64,
     ) -> ProgramResult {
-        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
-        if ghost.state != GhostState::Burned {
-            return Err(GhostError::InvalidState.into());
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"relayer", relayer.key.as_ref()], program_id);
+        if *stats_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if stats_account.data_is_empty() {
+            Self::create_pda_account(
+                program_id,
+                relayer,
+                stats_account,
+                system_program,
+                RelayerStats::space(),
+                &[b"relayer", relayer.key.as_ref(), &[bump]],
+            )?;
         }
-        ghost.state = GhostState::Minted;
-        ghost.mint_ts = Clock::get()?.unix_timestamp;
-        ghost.mint_proof = mint_proof;
-        ghost.destination_address[..32].copy_from_slice(&recipient.to_bytes());
 
-        Self::write_ghost(accounts, ghost)?;
-        let _ = config;
-        msg!("Ghost minted");
+        let now = Clock::get()?.unix_timestamp;
+        let mut stats: RelayerStats = if Self::is_blank(stats_account) {
+            RelayerStats {
+                relayer: *relayer.key,
+                window_start: now,
+                last_payment_ts: 0,
+                payments_in_window: 0,
+                volume_in_window: 0,
+            }
+        } else {
+            Self::load_or_legacy(stats_account)?
+        };
+        if now - stats.window_start >= config.relayer_window_secs {
+            stats.window_start = now;
+            stats.payments_in_window = 0;
+            stats.volume_in_window = 0;
+        }
+
+        let volume = stats.volume_in_window.saturating_add(amount);
+        if volume > config.relayer_window_cap {
+            msg!("Relayer rate limited: {} > {}", volume, config.relayer_window_cap);
+            return Err(GhostError::RateLimited.into());
+        }
+        stats.volume_in_window = volume;
+        stats.payments_in_window += 1;
+        stats.last_payment_ts = now;
+
+        Self::save_account(stats_account, &stats)?;
         Ok(())
     }
 
-    fn ack_remote(
+    /// Creates a program-owned PDA funded by `payer`. Anyone can send
+    /// lamports to an address before it is created, and create_account
+    /// refuses a funded one, so a pre-funded PDA is topped up to rent and
+    /// then allocated and assigned instead.
+    fn create_pda_account<'a>(
         program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        ghost_id: [u8; 32],
+        payer: &AccountInfo<'a>,
+        account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        space: usize,
+        seeds: &[&[u8]],
     ) -> ProgramResult {
-        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
-        if ghost.state != GhostState::Burned {
-            return Err(GhostError::InvalidState.into());
+        let lamports = Rent::get()?.minimum_balance(space);
+        if account.lamports() == 0 {
+            let create_ix = solana_program::system_instruction::create_account(
+                payer.key,
+                account.key,
+                lamports,
+                space as u64,
+                program_id,
+            );
+            return solana_program::program::invoke_signed(
+                &create_ix,
+                &[payer.clone(), account.clone(), system_program.clone()],
+                &[seeds],
+            );
         }
-        ghost.remote_ack = true;
-        Self::write_ghost(accounts, ghost)?;
-        let _ = config;
-        msg!("Remote mint acknowledged");
-        Ok(())
+
+        let top_up = lamports.saturating_sub(account.lamports());
+        if top_up > 0 {
+            let transfer_ix = solana_program::system_instruction::transfer(payer.key, account.key, top_up);
+            solana_program::program::invoke(
+                &transfer_ix,
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        let allocate_ix = solana_program::system_instruction::allocate(account.key, space as u64);
+        solana_program::program::invoke_signed(
+            &allocate_ix,
+            &[account.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+        let assign_ix = solana_program::system_instruction::assign(account.key, program_id);
+        solana_program::program::invoke_signed(
+            &assign_ix,
+            &[account.clone(), system_program.clone()],
+            &[seeds],
+        )
     }
 
-    fn destroy_ghost(
+    fn set_rate_limit(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        ghost_id: [u8; 32],
+        window_secs: i64,
+        max_volume: u64,
     ) -> ProgramResult {
-        let (config, mut ghost) = Self::load_with_validator(program_id, accounts, ghost_id)?;
-        if ghost.state != GhostState::Minted && !ghost.remote_ack {
-            return Err(GhostError::InvalidState.into());
-        }
-        ghost.state = GhostState::Settled;
-        Self::write_ghost(accounts, ghost)?;
-        let _ = config;
-        msg!("Ghost destroyed/settled");
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if window_secs < 0 {
+            return Err(GhostError::InvalidConfig.into());
+        }
+        config.relayer_window_secs = window_secs;
+        config.relayer_window_cap = max_volume;
+
+        Self::save_config(config_account, &config)?;
+        msg!("Relayer rate limit: {} lamports per {}s", max_volume, window_secs);
         Ok(())
     }
 
-    fn load_with_validator(
+    fn set_intent_cap(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        ghost_id: [u8; 32],
-    ) -> Result<(ProgramConfig, GhostAccount), ProgramError> {
+        intent_cap_bps: u16,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let config_account = next_account_info(account_info_iter)?;
-        let ghost_account = next_account_info(account_info_iter)?;
-        let validator = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
 
-        let config = Self::load_config(program_id, config_account)?;
-        config.assert_validator(validator.key)?;
-        if !validator.is_signer {
-            return Err(GhostError::MissingSigner.into());
-        }
-        if ghost_account.owner != program_id {
-            return Err(GhostError::IncorrectProgramId.into());
-        }
-        
-        let ghost: GhostAccount = GhostAccount::try_from_slice(&ghost_account.data.borrow())
-            .unwrap_or(GhostAccount {
-                ghost_id: [0u8; 32],
-                initiator: Pubkey::default(),
-                source_token: Pubkey::default(),
-                destination_token: Pubkey::default(),
-                destination_chain: 0,
-                destination_address: [0u8; 64],
-                state: GhostState::None,
-                amount: 0,
-                lock_ts: 0,
-                burn_ts: 0,
-                mint_ts: 0,
-                burn_proof: [0u8; 32],
-                mint_proof: [0u8; 32],
-                is_remote: false,
-                remote_ack: false,
-            });
-
-        if ghost.ghost_id != ghost_id && ghost.state != GhostState::None {
-            return Err(GhostError::GhostMismatch.into());
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+
+        if intent_cap_bps as u64 > BPS_DENOMINATOR {
+            return Err(GhostError::InvalidConfig.into());
         }
+        config.intent_cap_bps = intent_cap_bps;
 
-        Ok((config, ghost))
+        Self::save_config(config_account, &config)?;
+        msg!("Intent cap set to {} bps", intent_cap_bps);
+        Ok(())
     }
 
-    fn write_ghost(accounts: &[AccountInfo], ghost: GhostAccount) -> ProgramResult {
+    fn set_max_open_intents(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_open_intents: u32,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let _config_account = next_account_info(account_info_iter)?;
-        let ghost_account = next_account_info(account_info_iter)?;
-        let _validator = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
 
-        ghost
-            .serialize(&mut &mut ghost_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        config.max_open_intents = max_open_intents;
+
+        // Lowering the cap below the open count only blocks new intents
+        Self::save_config(config_account, &config)?;
+        msg!("Max open intents set to {} ({} open)", max_open_intents, config.open_intents);
         Ok(())
     }
 
-    // ═══════════════════════════════════════════════════════════════════════════════
-    // LIQUIDITY POOL FUNCTIONS
-    // ═══════════════════════════════════════════════════════════════════════════════
-
-    /// Initialize a new liquidity pool
-    fn initialize_pool(
+    fn set_relayer_bond(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        pool_seed: [u8; 32],
+        min_bond: u64,
+        cooldown: i64,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let pool_account = next_account_info(account_info_iter)?;
-        let authority = next_account_info(account_info_iter)?;
-
-        if !authority.is_signer {
-            return Err(GhostError::MissingSigner.into());
-        }
-        if pool_account.owner != program_id {
-            return Err(GhostError::IncorrectProgramId.into());
-        }
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
 
-        let pool = LiquidityPool {
-            seed: pool_seed,
-            total_deposited: 0,
-            total_shares: 0,
-            total_fees: 0,
-            available_liquidity: 0,
-            active: true,
-        };
+        let mut config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
 
-        pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        if cooldown < 0 {
+            return Err(GhostError::InvalidConfig.into());
+        }
+        config.relayer_min_bond = min_bond;
+        config.bond_cooldown = cooldown;
 
-        msg!("Liquidity pool initialized");
+        Self::save_config(config_account, &config)?;
+        msg!("Relayer bond: {} lamports, {}s cooldown", min_bond, cooldown);
         Ok(())
     }
 
-    /// Deposit SOL into the pool
-    fn deposit_to_pool(
+    /// Fails unless `bond_account` is the relayer's active bond holding at
+    /// least the configured minimum
+    fn ensure_bonded(
         program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        amount: u64,
+        config: &ProgramConfig,
+        relayer: &AccountInfo,
+        bond_account: &AccountInfo,
     ) -> ProgramResult {
+        let bond = Self::load_bond(program_id, relayer.key, bond_account)?;
+        if !bond.active || bond.amount < config.relayer_min_bond {
+            msg!("Relayer bond {} below required {}", bond.amount, config.relayer_min_bond);
+            return Err(GhostError::InsufficientBond.into());
+        }
+        Ok(())
+    }
+
+    /// Loads the bond PDA of `relayer`, which must already exist
+    fn load_bond(
+        program_id: &Pubkey,
+        relayer: &Pubkey,
+        bond_account: &AccountInfo,
+    ) -> Result<RelayerBond, ProgramError> {
+        let (expected, _) = Pubkey::find_program_address(&[b"bond", relayer.as_ref()], program_id);
+        if *bond_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if bond_account.owner != program_id || Self::is_blank(bond_account) {
+            msg!("Relayer {} has no bond", relayer);
+            return Err(GhostError::InsufficientBond.into());
+        }
+        Self::load_account(bond_account)
+    }
+
+    /// Accounts: relayer, bond PDA, system program
+    fn post_bond(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let pool_account = next_account_info(account_info_iter)?;
-        let lp_position_account = next_account_info(account_info_iter)?;
-        let depositor = next_account_info(account_info_iter)?;
+        let relayer = next_account_info(account_info_iter)?;
+        let bond_account = next_account_info(account_info_iter)?;
         let system_program = next_account_info(account_info_iter)?;
 
-        if !depositor.is_signer {
+        if !relayer.is_signer {
             return Err(GhostError::MissingSigner.into());
         }
-        if pool_account.owner != program_id {
-            return Err(GhostError::IncorrectProgramId.into());
+        let (expected, bump) =
+            Pubkey::find_program_address(&[b"bond", relayer.key.as_ref()], program_id);
+        if *bond_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
         }
-
-        // Load pool
-        let mut pool: LiquidityPool = LiquidityPool::try_from_slice(&pool_account.data.borrow())
-            .map_err(|_| GhostError::AccountDeserialization)?;
-
-        if !pool.active {
-            msg!("Pool not active");
-            return Err(ProgramError::InvalidAccountData);
+        if bond_account.data_is_empty() {
+            Self::create_pda_account(
+                program_id,
+                relayer,
+                bond_account,
+                system_program,
+                RelayerBond::space(),
+                &[b"bond", relayer.key.as_ref(), &[bump]],
+            )?;
         }
 
-        // Calculate shares
-        let shares = if pool.total_shares == 0 {
-            amount
+        let mut bond: RelayerBond = if Self::is_blank(bond_account) {
+            RelayerBond {
+                relayer: *relayer.key,
+                amount: 0,
+                active: false,
+                unbonded_at: 0,
+            }
         } else {
-            (amount as u128 * pool.total_shares as u128 / pool.total_deposited as u128) as u64
+            Self::load_account(bond_account)?
         };
 
-        // Transfer SOL from depositor to pool
         let transfer_ix = solana_program::system_instruction::transfer(
-            depositor.key,
-            pool_account.key,
+            relayer.key,
+            bond_account.key,
             amount,
         );
         solana_program::program::invoke(
             &transfer_ix,
-            &[depositor.clone(), pool_account.clone(), system_program.clone()],
+            &[relayer.clone(), bond_account.clone(), system_program.clone()],
         )?;
 
-        // Update pool
-        pool.total_deposited += amount;
-        pool.total_shares += shares;
-        pool.available_liquidity += amount;
-
-        pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
-
-        // Update LP position
-        let mut position: LPPosition = LPPosition::try_from_slice(&lp_position_account.data.borrow())
-            .unwrap_or(LPPosition {
-                owner: *depositor.key,
-                pool: pool.seed,
-                shares: 0,
-                deposited_at: 0,
-            });
-
-        position.shares += shares;
-        position.deposited_at = Clock::get()?.unix_timestamp;
-
-        position.serialize(&mut &mut lp_position_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        bond.amount = bond.amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        bond.active = true;
+        bond.unbonded_at = 0;
+        Self::save_account(bond_account, &bond)?;
 
-        msg!("Deposited {} lamports, received {} shares", amount, shares);
+        msg!("Relayer bonded {} lamports ({} total)", amount, bond.amount);
         Ok(())
     }
 
-    /// Withdraw SOL from the pool
-    fn withdraw_from_pool(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        shares: u64,
-    ) -> ProgramResult {
+    /// Accounts: relayer, bond PDA
+    fn unbond_relayer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let pool_account = next_account_info(account_info_iter)?;
-        let lp_position_account = next_account_info(account_info_iter)?;
-        let withdrawer = next_account_info(account_info_iter)?;
+        let relayer = next_account_info(account_info_iter)?;
+        let bond_account = next_account_info(account_info_iter)?;
 
-        if !withdrawer.is_signer {
+        if !relayer.is_signer {
             return Err(GhostError::MissingSigner.into());
         }
-        if pool_account.owner != program_id {
-            return Err(GhostError::IncorrectProgramId.into());
+        let mut bond = Self::load_bond(program_id, relayer.key, bond_account)?;
+        if !bond.active {
+            return Err(GhostError::InvalidState.into());
         }
+        bond.active = false;
+        bond.unbonded_at = Clock::get()?.unix_timestamp;
+        Self::save_account(bond_account, &bond)?;
 
-        // Load pool
-        let mut pool: LiquidityPool = LiquidityPool::try_from_slice(&pool_account.data.borrow())
-            .map_err(|_| GhostError::AccountDeserialization)?;
+        msg!("Relayer unbonded at {}", bond.unbonded_at);
+        Ok(())
+    }
 
-        // Load position
-        let mut position: LPPosition = LPPosition::try_from_slice(&lp_position_account.data.borrow())
-            .map_err(|_| GhostError::AccountDeserialization)?;
+    /// Accounts: config, relayer, bond PDA. The whole account balance,
+    /// bond and rent, goes back to the relayer.
+    fn release_bond(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let relayer = next_account_info(account_info_iter)?;
+        let bond_account = next_account_info(account_info_iter)?;
 
-        if position.owner != *withdrawer.key {
-            msg!("Not position owner");
-            return Err(ProgramError::InvalidAccountData);
+        let config = Self::load_config(program_id, config_account)?;
+        if !relayer.is_signer {
+            return Err(GhostError::MissingSigner.into());
         }
-        if position.shares < shares {
-            msg!("Insufficient shares");
-            return Err(ProgramError::InsufficientFunds);
+        let bond = Self::load_bond(program_id, relayer.key, bond_account)?;
+        if bond.active {
+            msg!("Bond must be unbonded before release");
+            return Err(GhostError::InvalidState.into());
         }
-
-        // Calculate withdrawal amount (includes earned fees)
-        let amount = (shares as u128 * pool.total_deposited as u128 / pool.total_shares as u128) as u64;
-
-        if pool.available_liquidity < amount {
-            msg!("Insufficient pool liquidity");
-            return Err(ProgramError::InsufficientFunds);
+        // The cooldown leaves a window to slash misbehavior found after unbonding
+        let now = Clock::get()?.unix_timestamp;
+        if now - bond.unbonded_at < config.bond_cooldown {
+            msg!("Bond locked until {}", bond.unbonded_at + config.bond_cooldown);
+            return Err(GhostError::TooEarly.into());
         }
 
-        // Transfer SOL from pool to withdrawer
-        **pool_account.try_borrow_mut_lamports()? -= amount;
-        **withdrawer.try_borrow_mut_lamports()? += amount;
+        let lamports = bond_account.lamports();
+        **bond_account.try_borrow_mut_lamports()? = 0;
+        **relayer.try_borrow_mut_lamports()? += lamports;
+        bond_account.data.borrow_mut().fill(0);
 
-        // Update pool
-        pool.total_deposited -= amount;
-        pool.total_shares -= shares;
-        pool.available_liquidity -= amount;
+        msg!("Bond released: {} lamports", lamports);
+        Ok(())
+    }
 
-        pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+    /// Accounts: config, admin, bond PDA, treasury
+    fn slash_relayer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        relayer: Pubkey,
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let admin = next_account_info(account_info_iter)?;
+        let bond_account = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
 
-        // Update position
-        position.shares -= shares;
+        let config = Self::load_config(program_id, config_account)?;
+        Self::ensure_admin(&config, admin, accounts)?;
+        if config.treasury == Pubkey::default() || *treasury.key != config.treasury {
+            msg!("Treasury mismatch");
+            return Err(GhostError::InvalidConfig.into());
+        }
 
-        position.serialize(&mut &mut lp_position_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        let mut bond = Self::load_bond(program_id, &relayer, bond_account)?;
+        let slashed = amount.min(bond.amount);
+        **bond_account.try_borrow_mut_lamports()? -= slashed;
+        **treasury.try_borrow_mut_lamports()? += slashed;
+        bond.amount -= slashed;
+        Self::save_account(bond_account, &bond)?;
 
-        msg!("Withdrew {} lamports for {} shares", amount, shares);
+        msg!("Relayer {} slashed {} lamports ({} left)", relayer, slashed, bond.amount);
         Ok(())
     }
 
-    /// Execute a cross-chain payment (sends SOL from pool to recipient)
-    fn execute_payment(
+    /// Accounts: config, intent, relayer. Intents recorded before the relayer
+    /// was tracked may be closed by any validator. Cancelled intents close
+    /// the same way as executed ones.
+    fn close_intent(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         intent_id: [u8; 32],
-        recipient: Pubkey,
-        amount: u64,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let config_account = next_account_info(account_info_iter)?;
-        let pool_account = next_account_info(account_info_iter)?;
-        let recipient_account = next_account_info(account_info_iter)?;
+        let intent_account = next_account_info(account_info_iter)?;
         let relayer = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, intent_account])?;
 
-        // Verify relayer is authorized
         let config = Self::load_config(program_id, config_account)?;
-        config.assert_validator(relayer.key)?;
-
         if !relayer.is_signer {
             return Err(GhostError::MissingSigner.into());
         }
-        if pool_account.owner != program_id {
+        if intent_account.owner != program_id {
             return Err(GhostError::IncorrectProgramId.into());
         }
-        if *recipient_account.key != recipient {
-            msg!("Recipient mismatch");
+
+        let intent: PaymentIntent = Self::load_or_legacy(intent_account)?;
+        if intent.intent_id != intent_id {
+            msg!("Intent mismatch");
             return Err(ProgramError::InvalidAccountData);
         }
+        if !intent.executed && !intent.cancelled {
+            return Err(GhostError::InvalidState.into());
+        }
+        if intent.relayer == Pubkey::default() {
+            config.assert_validator(relayer.key)?;
+        } else if intent.relayer != *relayer.key {
+            msg!("Rent belongs to relayer {}", intent.relayer);
+            return Err(GhostError::InvalidRecipient.into());
+        }
 
-        // Load pool
-        let mut pool: LiquidityPool = LiquidityPool::try_from_slice(&pool_account.data.borrow())
-            .map_err(|_| GhostError::AccountDeserialization)?;
+        // Keep a record of the intent at its address so it can't be recorded
+        // again, and hand back the rent the rest of the account held
+        intent_account.data.borrow_mut().fill(0);
+        intent_account.realloc(ClosedIntent::space(), true)?;
+        let closed = ClosedIntent {
+            intent_id,
+            closed_at: Clock::get()?.unix_timestamp,
+        };
+        Self::save_account(intent_account, &closed)?;
+        let retained = Rent::get()?.minimum_balance(ClosedIntent::space());
+        let lamports = intent_account.lamports().saturating_sub(retained);
+        **intent_account.try_borrow_mut_lamports()? -= lamports;
+        **relayer.try_borrow_mut_lamports()? += lamports;
 
-        if pool.available_liquidity < amount {
-            msg!("Insufficient pool liquidity: {} < {}", pool.available_liquidity, amount);
-            return Err(ProgramError::InsufficientFunds);
+        msg!("Intent closed: {} lamports reclaimed", lamports);
+        Ok(())
+    }
+
+    /// Takes a finished intent out of the config's open_intents. Only intents
+    /// that were counted when recorded are taken out, so ones recorded before
+    /// the counter existed can't pull it below the intents still open.
+    fn uncount_intent(config: &mut ProgramConfig, intent: &mut PaymentIntent) -> Result<bool, ProgramError> {
+        if !intent.counted {
+            return Ok(false);
         }
+        config.open_intents = config.open_intents.checked_sub(1).ok_or_else(|| {
+            msg!("Counted intent but no intents open");
+            GhostError::InvalidState
+        })?;
+        intent.counted = false;
+        Ok(true)
+    }
 
-        // Transfer SOL from pool to recipient
-        **pool_account.try_borrow_mut_lamports()? -= amount;
-        **recipient_account.try_borrow_mut_lamports()? += amount;
+    /// Accounts: config, intent, validator. Whatever was paid out before the
+    /// cancel stays paid; the intent just stops accepting payouts.
+    fn cancel_intent(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        intent_id: [u8; 32],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let config_account = next_account_info(account_info_iter)?;
+        let intent_account = next_account_info(account_info_iter)?;
+        let validator = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, intent_account])?;
 
-        // Update pool
-        pool.available_liquidity -= amount;
+        let mut config = Self::load_config(program_id, config_account)?;
+        config.assert_validator(validator.key)?;
+        if !validator.is_signer {
+            return Err(GhostError::MissingSigner.into());
+        }
+        if intent_account.owner != program_id {
+            return Err(GhostError::IncorrectProgramId.into());
+        }
 
-        pool.serialize(&mut &mut pool_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        let mut intent: PaymentIntent = Self::load_or_legacy(intent_account)?;
+        if intent.intent_id != intent_id {
+            msg!("Intent mismatch");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if intent.executed || intent.cancelled {
+            return Err(GhostError::InvalidState.into());
+        }
 
-        msg!("Payment executed: {} lamports to {} (intent: {:?})", 
-            amount, recipient, &intent_id[..8]);
+        intent.cancelled = true;
+        if Self::uncount_intent(&mut config, &mut intent)? {
+            Self::write_config_header(config_account, &config)?;
+        }
+        Self::save_account(intent_account, &intent)?;
+        msg!("Intent cancelled after {} of {} paid", intent.amount_paid, intent.amount);
         Ok(())
     }
 
-    /// Record an incoming payment intent from another chain
+    /// Record an incoming payment intent from another chain.
+    /// Accounts: config, intent PDA, relayer, pool registry PDA, pool, system
+    /// program, then the relayer bond PDA when bonds are required.
     fn record_payment_intent(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
@@ -872,18 +6062,68 @@ impl Processor {
         let config_account = next_account_info(account_info_iter)?;
         let intent_account = next_account_info(account_info_iter)?;
         let relayer = next_account_info(account_info_iter)?;
+        let registry_account = next_account_info(account_info_iter)?;
+        let pool_account = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+        Self::ensure_distinct(&[config_account, intent_account, registry_account, pool_account])?;
 
         // Verify relayer is authorized
-        let config = Self::load_config(program_id, config_account)?;
+        let mut config = Self::load_config(program_id, config_account)?;
+        config.ensure_not_paused()?;
         config.assert_validator(relayer.key)?;
 
         if !relayer.is_signer {
             return Err(GhostError::MissingSigner.into());
         }
-        if intent_account.owner != program_id {
-            return Err(GhostError::IncorrectProgramId.into());
+        // One address per intent, so a closed intent's record blocks replays
+        let (expected, bump) = Pubkey::find_program_address(&[b"intent", &intent_id], program_id);
+        if *intent_account.key != expected {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if system_program.key != &system_program::id() {
+            return Err(GhostError::InvalidSystemProgram.into());
+        }
+
+        // Reject intents for tokens no pool can pay out
+        let registry = Self::resolve_pool(program_id, registry_account, &dest_token)?;
+        if registry.pool != *pool_account.key {
+            msg!("Pool does not back token {}", dest_token);
+            return Err(GhostError::PoolTokenMismatch.into());
+        }
+        // Bound what a single rogue intent can claim against the pool
+        let pool = Self::load_pool(pool_account)?;
+        let cap = config.intent_cap(pool.total_deposited);
+        if amount > cap {
+            msg!("Intent amount exceeds cap: {} > {}", amount, cap);
+            return Err(GhostError::AmountExceedsLimit.into());
+        }
+        // Bonded relayers only: accounts continue with the relayer bond PDA
+        if config.relayer_min_bond > 0 {
+            Self::ensure_bonded(program_id, &config, relayer, next_account_info(account_info_iter)?)?;
         }
 
+        // Recording over an existing or closed intent would reset what it has
+        // paid out and let it be paid again
+        if !Self::is_blank(intent_account) {
+            msg!("Intent account already holds an intent");
+            return Err(GhostError::InvalidState.into());
+        }
+        if config.max_open_intents > 0 && config.open_intents >= config.max_open_intents {
+            msg!("{} intents already open", config.open_intents);
+            return Err(GhostError::TooManyIntents.into());
+        }
+        config.open_intents += 1;
+        Self::write_config_header(config_account, &config)?;
+
+        Self::create_pda_account(
+            program_id,
+            relayer,
+            intent_account,
+            system_program,
+            PaymentIntent::space(),
+            &[b"intent", &intent_id, &[bump]],
+        )?;
+
         let intent = PaymentIntent {
             intent_id,
             sender_chain,
@@ -893,30 +6133,201 @@ impl Processor {
             recipient: Pubkey::default(), // Set when executed
             executed: false,
             timestamp: Clock::get()?.unix_timestamp,
+            amount_paid: 0,
+            relayer: *relayer.key,
+            cancelled: false,
+            counted: true,
         };
 
-        intent.serialize(&mut &mut intent_account.data.borrow_mut()[..])
-            .map_err(|_| GhostError::AccountSerialization)?;
+        Self::save_account(intent_account, &intent)?;
 
         msg!("Payment intent recorded: {:?}", &intent_id[..8]);
         Ok(())
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Custom error codes. Discriminants are part of the client-facing ABI:
+/// never renumber, and give new variants the next free number (then add
+/// them to `from_code` and `name`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
 pub enum GhostError {
-    InvalidInstruction,
-    AccountSerialization,
-    AccountDeserialization,
-    UnauthorizedAdmin,
-    ValidatorExists,
-    ValidatorLimit,
-    MissingSigner,
-    UnauthorizedValidator,
-    IncorrectProgramId,
-    GhostExists,
-    GhostMismatch,
-    InvalidState,
+    InvalidInstruction = 0,
+    AccountSerialization = 1,
+    AccountDeserialization = 2,
+    UnauthorizedAdmin = 3,
+    ValidatorExists = 4,
+    ValidatorLimit = 5,
+    MissingSigner = 6,
+    UnauthorizedValidator = 7,
+    IncorrectProgramId = 8,
+    GhostExists = 9,
+    GhostMismatch = 10,
+    InvalidState = 11,
+    InsufficientLiquidity = 12,
+    InvalidConfig = 13,
+    ThresholdNotMet = 14,
+    InvalidProof = 15,
+    InitiatorMismatch = 16,
+    UnknownEmitter = 17,
+    VaaReplayed = 18,
+    InvalidRecipient = 19,
+    RateLimited = 20,
+    InvalidDestination = 21,
+    AccountVersionMismatch = 22,
+    PoolNotRegistered = 23,
+    LiquidityExceedsDeposits = 24,
+    PoolUnderfunded = 25,
+    SharesDepositMismatch = 26,
+    RecipientMismatch = 27,
+    Overpayment = 28,
+    Paused = 29,
+    SlippageExceeded = 30,
+    TooEarly = 31,
+    BalanceMismatch = 32,
+    BelowMinimumDeposit = 33,
+    PoolCapExceeded = 34,
+    InvalidRoute = 35,
+    WithdrawLocked = 36,
+    NotInitialized = 37,
+    ChainDisabled = 38,
+    PoolTokenMismatch = 39,
+    InsufficientBond = 40,
+    AmountExceedsLimit = 41,
+    NonMonotonicTimestamp = 42,
+    ZeroShares = 43,
+    BelowMinimumWithdrawal = 44,
+    BreakerTripped = 45,
+    DuplicateAccount = 46,
+    NonceOutOfOrder = 47,
+    InvalidTokenAccount = 48,
+    InvalidSystemProgram = 49,
+    TooManyIntents = 50,
+    GhostNotInitialized = 51,
+}
+
+impl GhostError {
+    pub fn from_code(code: u32) -> Option<Self> {
+        use GhostError::*;
+        Some(match code {
+            0 => InvalidInstruction,
+            1 => AccountSerialization,
+            2 => AccountDeserialization,
+            3 => UnauthorizedAdmin,
+            4 => ValidatorExists,
+            5 => ValidatorLimit,
+            6 => MissingSigner,
+            7 => UnauthorizedValidator,
+            8 => IncorrectProgramId,
+            9 => GhostExists,
+            10 => GhostMismatch,
+            11 => InvalidState,
+            12 => InsufficientLiquidity,
+            13 => InvalidConfig,
+            14 => ThresholdNotMet,
+            15 => InvalidProof,
+            16 => InitiatorMismatch,
+            17 => UnknownEmitter,
+            18 => VaaReplayed,
+            19 => InvalidRecipient,
+            20 => RateLimited,
+            21 => InvalidDestination,
+            22 => AccountVersionMismatch,
+            23 => PoolNotRegistered,
+            24 => LiquidityExceedsDeposits,
+            25 => PoolUnderfunded,
+            26 => SharesDepositMismatch,
+            27 => RecipientMismatch,
+            28 => Overpayment,
+            29 => Paused,
+            30 => SlippageExceeded,
+            31 => TooEarly,
+            32 => BalanceMismatch,
+            33 => BelowMinimumDeposit,
+            34 => PoolCapExceeded,
+            35 => InvalidRoute,
+            36 => WithdrawLocked,
+            37 => NotInitialized,
+            38 => ChainDisabled,
+            39 => PoolTokenMismatch,
+            40 => InsufficientBond,
+            41 => AmountExceedsLimit,
+            42 => NonMonotonicTimestamp,
+            43 => ZeroShares,
+            44 => BelowMinimumWithdrawal,
+            45 => BreakerTripped,
+            46 => DuplicateAccount,
+            47 => NonceOutOfOrder,
+            48 => InvalidTokenAccount,
+            49 => InvalidSystemProgram,
+            50 => TooManyIntents,
+            51 => GhostNotInitialized,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        use GhostError::*;
+        match self {
+            InvalidInstruction => "InvalidInstruction",
+            AccountSerialization => "AccountSerialization",
+            AccountDeserialization => "AccountDeserialization",
+            UnauthorizedAdmin => "UnauthorizedAdmin",
+            ValidatorExists => "ValidatorExists",
+            ValidatorLimit => "ValidatorLimit",
+            MissingSigner => "MissingSigner",
+            UnauthorizedValidator => "UnauthorizedValidator",
+            IncorrectProgramId => "IncorrectProgramId",
+            GhostExists => "GhostExists",
+            GhostMismatch => "GhostMismatch",
+            InvalidState => "InvalidState",
+            InsufficientLiquidity => "InsufficientLiquidity",
+            InvalidConfig => "InvalidConfig",
+            ThresholdNotMet => "ThresholdNotMet",
+            InvalidProof => "InvalidProof",
+            InitiatorMismatch => "InitiatorMismatch",
+            UnknownEmitter => "UnknownEmitter",
+            VaaReplayed => "VaaReplayed",
+            InvalidRecipient => "InvalidRecipient",
+            RateLimited => "RateLimited",
+            InvalidDestination => "InvalidDestination",
+            AccountVersionMismatch => "AccountVersionMismatch",
+            PoolNotRegistered => "PoolNotRegistered",
+            LiquidityExceedsDeposits => "LiquidityExceedsDeposits",
+            PoolUnderfunded => "PoolUnderfunded",
+            SharesDepositMismatch => "SharesDepositMismatch",
+            RecipientMismatch => "RecipientMismatch",
+            Overpayment => "Overpayment",
+            Paused => "Paused",
+            SlippageExceeded => "SlippageExceeded",
+            TooEarly => "TooEarly",
+            BalanceMismatch => "BalanceMismatch",
+            BelowMinimumDeposit => "BelowMinimumDeposit",
+            PoolCapExceeded => "PoolCapExceeded",
+            InvalidRoute => "InvalidRoute",
+            WithdrawLocked => "WithdrawLocked",
+            NotInitialized => "NotInitialized",
+            ChainDisabled => "ChainDisabled",
+            PoolTokenMismatch => "PoolTokenMismatch",
+            InsufficientBond => "InsufficientBond",
+            AmountExceedsLimit => "AmountExceedsLimit",
+            NonMonotonicTimestamp => "NonMonotonicTimestamp",
+            ZeroShares => "ZeroShares",
+            BelowMinimumWithdrawal => "BelowMinimumWithdrawal",
+            BreakerTripped => "BreakerTripped",
+            DuplicateAccount => "DuplicateAccount",
+            NonceOutOfOrder => "NonceOutOfOrder",
+            InvalidTokenAccount => "InvalidTokenAccount",
+            InvalidSystemProgram => "InvalidSystemProgram",
+            TooManyIntents => "TooManyIntents",
+            GhostNotInitialized => "GhostNotInitialized",
+        }
+    }
+}
+
+/// Resolves a `ProgramError::Custom` code from this program to its name
+pub fn error_name(code: u32) -> Option<&'static str> {
+    GhostError::from_code(code).map(GhostError::name)
 }
 
 impl From<GhostError> for ProgramError {
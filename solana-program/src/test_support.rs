@@ -0,0 +1,406 @@
+//! Fixtures for driving the processor directly from Rust tests.
+//!
+//! Compiled only with the `test-support` feature, which also implies
+//! `no-entrypoint` so a test binary can link this crate next to others.
+//! Accounts live in memory as `AccountFixture`s and are handed to the
+//! processor as `AccountInfo`s laid out the way the runtime serializes them,
+//! so `realloc` works. `install_sysvars` answers Clock and Rent reads and
+//! carries out System Program CPIs (transfer, create, allocate, assign);
+//! any other CPI fails rather than being skipped.
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::sync::{Mutex, MutexGuard};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+    instruction::Instruction,
+    msg,
+    program_error::ProgramError,
+    program_stubs::{self, SyscallStubs},
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    system_instruction::SystemInstruction,
+    system_program,
+    sysvar::rent::Rent,
+};
+
+use crate::{
+    account_version, process_instruction, GhostAccount, GhostInstruction, GhostState, LiquidityPool, PoolData,
+    Processor, ProgramConfig, ACCOUNT_HEADER_LEN, ACCOUNT_VERSION, POOL_DATA_OFFSET,
+};
+
+/// Key preceded by the original data length, which `AccountInfo::realloc`
+/// reads from just before the key
+#[repr(C)]
+#[derive(Clone, Debug)]
+struct KeyHeader {
+    original_data_len: u32,
+    key: Pubkey,
+}
+
+/// An account owned by the test, borrowed as an `AccountInfo` per call
+#[derive(Clone, Debug)]
+pub struct AccountFixture {
+    header: KeyHeader,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    // Data length, the data, then realloc headroom; u64 words keep the data
+    // 8-byte aligned for zero-copy accounts
+    buffer: Vec<u64>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub executable: bool,
+}
+
+impl AccountFixture {
+    /// Writable, rent-exempt, zeroed account of `space` bytes
+    pub fn new(key: Pubkey, owner: Pubkey, space: usize) -> Self {
+        let words = 1 + (space + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+        let mut buffer = vec![0u64; words];
+        buffer[0] = space as u64;
+        AccountFixture {
+            header: KeyHeader {
+                original_data_len: space as u32,
+                key,
+            },
+            owner,
+            lamports: Rent::default().minimum_balance(space),
+            buffer,
+            is_signer: false,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    /// System-owned signing wallet holding `lamports`
+    pub fn signer(key: Pubkey, lamports: u64) -> Self {
+        AccountFixture {
+            lamports,
+            is_signer: true,
+            ..Self::new(key, system_program::id(), 0)
+        }
+    }
+
+    /// System-owned, empty account that a handler will create
+    pub fn unallocated(key: Pubkey) -> Self {
+        AccountFixture {
+            lamports: 0,
+            ..Self::new(key, system_program::id(), 0)
+        }
+    }
+
+    /// Read-only placeholder for a program or sysvar the handler only
+    /// compares by key
+    pub fn readonly(key: Pubkey) -> Self {
+        AccountFixture {
+            is_writable: false,
+            ..Self::new(key, system_program::id(), 0)
+        }
+    }
+
+    pub fn key(&self) -> Pubkey {
+        self.header.key
+    }
+
+    /// The account's data at its current length
+    pub fn data(&self) -> &[u8] {
+        let len = self.buffer[0] as usize;
+        &bytemuck::cast_slice(&self.buffer[1..])[..len]
+    }
+
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        let len = self.buffer[0] as usize;
+        &mut bytemuck::cast_slice_mut(&mut self.buffer[1..])[..len]
+    }
+
+    /// Decodes a borsh account written by the processor
+    pub fn state<T: BorshDeserialize>(&self) -> T {
+        assert_eq!(account_version(self.data()), Some(ACCOUNT_VERSION), "account is not at ACCOUNT_VERSION");
+        let mut body = &self.data()[ACCOUNT_HEADER_LEN..];
+        T::deserialize(&mut body).expect("account decodes")
+    }
+
+    /// Decodes a zero-copy pool account
+    pub fn pool(&self) -> LiquidityPool {
+        let size = core::mem::size_of::<PoolData>();
+        let data: &PoolData =
+            bytemuck::from_bytes(&self.data()[POOL_DATA_OFFSET..POOL_DATA_OFFSET + size]);
+        LiquidityPool::from(data)
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        let len = self.buffer[0] as usize;
+        // Realloc limits are measured from the length at the start of the call
+        self.header.original_data_len = len as u32;
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut self.buffer[..]);
+        AccountInfo::new(
+            &self.header.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut bytes[8..8 + len],
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+/// Borrows every fixture, in order, as the accounts of one instruction
+pub fn account_infos(fixtures: &mut [AccountFixture]) -> Vec<AccountInfo<'_>> {
+    fixtures.iter_mut().map(AccountFixture::info).collect()
+}
+
+/// Encodes `instruction` the way clients do and runs it through the same
+/// decode-and-dispatch path as the entrypoint
+pub fn process(
+    program_id: &Pubkey,
+    fixtures: &mut [AccountFixture],
+    instruction: &GhostInstruction,
+) -> ProgramResult {
+    let data = instruction.pack();
+    let accounts = account_infos(fixtures);
+    process_instruction(program_id, &accounts, &data)
+}
+
+/// Accounts shared by a sequence of instructions, each instruction naming
+/// the ones it takes by key
+#[derive(Default)]
+pub struct Bank {
+    accounts: Vec<AccountFixture>,
+}
+
+impl Bank {
+    /// Adds `fixture` (replacing any account with its key) and returns its key
+    pub fn add(&mut self, fixture: AccountFixture) -> Pubkey {
+        let key = fixture.key();
+        self.accounts.retain(|account| account.key() != key);
+        self.accounts.push(fixture);
+        key
+    }
+
+    pub fn get(&self, key: &Pubkey) -> &AccountFixture {
+        self.accounts
+            .iter()
+            .find(|account| account.key() == *key)
+            .expect("account is in the bank")
+    }
+
+    pub fn get_mut(&mut self, key: &Pubkey) -> &mut AccountFixture {
+        self.accounts
+            .iter_mut()
+            .find(|account| account.key() == *key)
+            .expect("account is in the bank")
+    }
+
+    /// Runs `instruction` over the accounts at `keys`. Like the runtime, a
+    /// failed instruction leaves every account as it was.
+    pub fn process(&mut self, program_id: &Pubkey, keys: &[Pubkey], instruction: &GhostInstruction) -> ProgramResult {
+        let mut fixtures: Vec<AccountFixture> = keys.iter().map(|key| self.get(key).clone()).collect();
+        process(program_id, &mut fixtures, instruction)?;
+        for fixture in fixtures {
+            let key = fixture.key();
+            *self.get_mut(&key) = fixture;
+        }
+        Ok(())
+    }
+}
+
+/// Answers sysvar reads and System Program CPIs off-chain
+struct FixtureSysvars {
+    clock: Clock,
+    rent: Rent,
+}
+
+impl FixtureSysvars {
+    /// Carries out `instruction` against the caller's accounts. Signatures
+    /// are not checked: the runtime would have rejected an unsigned payer
+    /// before the handler under test could observe the result.
+    fn system_instruction(instruction: &Instruction, accounts: &[AccountInfo]) -> ProgramResult {
+        let account = |index: usize| {
+            let meta = instruction
+                .accounts
+                .get(index)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            accounts
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)
+        };
+        let transfer = |from: &AccountInfo, to: &AccountInfo, lamports: u64| -> ProgramResult {
+            let balance = from.lamports();
+            **from.try_borrow_mut_lamports()? = balance
+                .checked_sub(lamports)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            **to.try_borrow_mut_lamports()? += lamports;
+            Ok(())
+        };
+        let allocate = |info: &AccountInfo, space: u64| -> ProgramResult {
+            if info.data_len() != 0 || info.owner != &system_program::id() {
+                msg!("Allocate: account {} already in use", info.key);
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            info.realloc(space as usize, true)
+        };
+
+        match limited_deserialize(&instruction.data, 1024)
+            .map_err(|_| ProgramError::InvalidInstructionData)?
+        {
+            SystemInstruction::Transfer { lamports } => transfer(account(0)?, account(1)?, lamports),
+            SystemInstruction::CreateAccount { lamports, space, owner } => {
+                let new_account = account(1)?;
+                if new_account.lamports() > 0 {
+                    msg!("CreateAccount: account {} already in use", new_account.key);
+                    return Err(ProgramError::AccountAlreadyInitialized);
+                }
+                transfer(account(0)?, new_account, lamports)?;
+                allocate(new_account, space)?;
+                new_account.assign(&owner);
+                Ok(())
+            }
+            SystemInstruction::Allocate { space } => allocate(account(0)?, space),
+            SystemInstruction::Assign { owner } => {
+                account(0)?.assign(&owner);
+                Ok(())
+            }
+            other => {
+                msg!("Fixture CPI does not support {:?}", other);
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+    }
+}
+
+impl SyscallStubs for FixtureSysvars {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: the sysvar getter passes a pointer to a Clock
+        unsafe { *(var_addr as *mut Clock) = self.clock.clone() };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: the sysvar getter passes a pointer to a Rent
+        unsafe { *(var_addr as *mut Rent) = self.rent };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id != system_program::id() {
+            msg!("Fixture CPI into {} is not supported", instruction.program_id);
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::system_instruction(instruction, account_infos)
+    }
+}
+
+static SYSVARS: Mutex<()> = Mutex::new(());
+
+/// Holds the process-wide syscall stubs for one test; tests that install
+/// sysvars run one at a time for as long as their guard lives
+pub struct SysvarGuard {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl SysvarGuard {
+    /// Moves `Clock::get()` to `unix_timestamp`
+    pub fn set_time(&self, unix_timestamp: i64) {
+        set_stubs(unix_timestamp);
+    }
+}
+
+fn set_stubs(unix_timestamp: i64) {
+    let clock = Clock {
+        unix_timestamp,
+        ..Clock::default()
+    };
+    program_stubs::set_syscall_stubs(Box::new(FixtureSysvars {
+        clock,
+        rent: Rent::default(),
+    }));
+}
+
+/// Makes `Clock::get()` report `unix_timestamp`, `Rent::get()` the default
+/// rent, and System Program CPIs move lamports between fixtures
+pub fn install_sysvars(unix_timestamp: i64) -> SysvarGuard {
+    // A test that panicked while holding the lock leaves nothing to repair
+    let lock = SYSVARS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    set_stubs(unix_timestamp);
+    SysvarGuard { _lock: lock }
+}
+
+/// Initialized config with `validators` enrolled and room for `max_validators`
+pub fn config(admin: Pubkey, validators: &[Pubkey], validator_threshold: u8, max_validators: u8) -> ProgramConfig {
+    ProgramConfig {
+        admin,
+        validator_threshold,
+        max_validators,
+        validators: validators.to_vec(),
+        ..ProgramConfig::default()
+    }
+}
+
+/// Config account at `key` holding `config` in the slotted layout
+pub fn config_account(program_id: &Pubkey, key: Pubkey, config: &ProgramConfig) -> AccountFixture {
+    let space = ProgramConfig::space(config.max_validators as usize);
+    let mut fixture = AccountFixture::new(key, *program_id, space);
+    Processor::save_config(&fixture.info(), config).expect("config fits its own space");
+    fixture
+}
+
+/// Decodes the config held by `fixture`
+pub fn config_state(program_id: &Pubkey, fixture: &mut AccountFixture) -> ProgramConfig {
+    Processor::load_config(program_id, &fixture.info()).expect("config decodes")
+}
+
+/// Active, empty pool
+pub fn pool(seed: [u8; 32]) -> LiquidityPool {
+    LiquidityPool {
+        seed,
+        active: true,
+        ..LiquidityPool::default()
+    }
+}
+
+/// Pool account at `key` in the zero-copy layout, funded with rent plus the
+/// pool's available liquidity and fees so its invariants hold
+pub fn pool_account(program_id: &Pubkey, key: Pubkey, pool: &LiquidityPool) -> AccountFixture {
+    let mut fixture = AccountFixture::new(key, *program_id, LiquidityPool::space());
+    fixture.lamports += pool.available_liquidity + pool.total_fees;
+    Processor::init_pool_data(&fixture.info(), pool).expect("pool fits its own space");
+    fixture
+}
+
+/// Local ghost in `state` for `amount` lamports
+pub fn ghost(ghost_id: [u8; 32], initiator: Pubkey, state: GhostState, amount: u64) -> GhostAccount {
+    GhostAccount {
+        ghost_id,
+        initiator,
+        state,
+        amount,
+        ..GhostAccount::default()
+    }
+}
+
+/// Ghost account at `key` holding `ghost`
+pub fn ghost_account(program_id: &Pubkey, key: Pubkey, ghost: &GhostAccount) -> AccountFixture {
+    let mut fixture = AccountFixture::new(key, *program_id, GhostAccount::space());
+    Processor::save_account(&fixture.info(), ghost).expect("ghost fits its own space");
+    fixture
+}
+
+/// Program-owned account at `key` holding `value` in the borsh layout
+pub fn borsh_account<T: BorshSerialize>(program_id: &Pubkey, key: Pubkey, space: usize, value: &T) -> AccountFixture {
+    let mut fixture = AccountFixture::new(key, *program_id, space);
+    Processor::save_account(&fixture.info(), value).expect("value fits its space");
+    fixture
+}
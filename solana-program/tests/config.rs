@@ -0,0 +1,113 @@
+use borsh::BorshSerialize;
+use ghost_wallet_solana::test_support::{self, AccountFixture, Bank};
+use ghost_wallet_solana::{
+    account_version, ChainConfig, GhostError, GhostInstruction, ProgramConfig, ACCOUNT_HEADER_LEN,
+    CONFIG_VERSION,
+};
+use solana_program::{pubkey::Pubkey, system_program};
+
+fn set_validator(validator: Pubkey, enabled: bool) -> GhostInstruction {
+    GhostInstruction::SetValidator { validator, enabled }
+}
+
+#[test]
+fn initialize_then_enroll_validators() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let mut accounts = [
+        AccountFixture::new(Pubkey::new_unique(), program_id, ProgramConfig::space(2)),
+        AccountFixture::signer(admin, 1_000_000_000),
+    ];
+
+    let initialize = GhostInstruction::Initialize {
+        admin,
+        validator_threshold: 1,
+        max_validators: 2,
+    };
+    test_support::process(&program_id, &mut accounts, &initialize).unwrap();
+
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+    test_support::process(&program_id, &mut accounts, &set_validator(first, true)).unwrap();
+    test_support::process(&program_id, &mut accounts, &set_validator(second, true)).unwrap();
+    assert_eq!(
+        test_support::process(&program_id, &mut accounts, &set_validator(Pubkey::new_unique(), true)),
+        Err(GhostError::ValidatorLimit.into())
+    );
+
+    // Removing the first swaps the last validator into its slot
+    test_support::process(&program_id, &mut accounts, &set_validator(first, false)).unwrap();
+    let config = test_support::config_state(&program_id, &mut accounts[0]);
+    assert_eq!(config.admin, admin);
+    assert_eq!(config.validators, vec![second]);
+}
+
+#[test]
+fn set_validator_requires_the_admin() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let config = test_support::config(admin, &[], 1, 4);
+    let mut accounts = [
+        test_support::config_account(&program_id, Pubkey::new_unique(), &config),
+        AccountFixture::signer(Pubkey::new_unique(), 1_000_000_000),
+    ];
+
+    assert_eq!(
+        test_support::process(&program_id, &mut accounts, &set_validator(Pubkey::new_unique(), true)),
+        Err(GhostError::UnauthorizedAdmin.into())
+    );
+    assert!(test_support::config_state(&program_id, &mut accounts[0]).validators.is_empty());
+}
+
+#[test]
+fn legacy_configs_are_upgraded_when_written() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    // A first byte equal to CONFIG_VERSION must not pass for a versioned config
+    let admin = Pubkey::new_from_array([CONFIG_VERSION; 32]);
+    let enrolled = Pubkey::new_unique();
+    let mut legacy = AccountFixture::new(Pubkey::new_unique(), program_id, ProgramConfig::space(4));
+    let bare = (admin, 1u8, 4u8, vec![enrolled]).try_to_vec().unwrap();
+    legacy.data_mut()[..bare.len()].copy_from_slice(&bare);
+    let mut accounts = [legacy, AccountFixture::signer(admin, 1_000_000_000)];
+
+    let config = test_support::config_state(&program_id, &mut accounts[0]);
+    assert_eq!((config.admin, config.validators.clone()), (admin, vec![enrolled]));
+
+    let added = Pubkey::new_unique();
+    test_support::process(&program_id, &mut accounts, &set_validator(added, true)).unwrap();
+    assert_eq!(account_version(accounts[0].data()), Some(CONFIG_VERSION));
+    let config = test_support::config_state(&program_id, &mut accounts[0]);
+    assert_eq!(config.admin, admin);
+    assert_eq!(config.validators, vec![enrolled, added]);
+}
+
+#[test]
+fn finality_delays_grow_short_chain_entries() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let mut bank = Bank::default();
+    let config = test_support::config(admin, &[], 1, 4);
+    let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+    bank.add(AccountFixture::signer(admin, 1_000_000_000));
+    bank.add(AccountFixture::readonly(system_program::id()));
+
+    // Registered when an entry was only its id and enabled flag
+    let chain_id = 1u64;
+    let (chain, _) = Pubkey::find_program_address(&[b"chain", &chain_id.to_le_bytes()], &program_id);
+    let short = ACCOUNT_HEADER_LEN + 8 + 1;
+    bank.add(test_support::borsh_account(&program_id, chain, short, &(chain_id, true)));
+
+    let finality = GhostInstruction::SetChainFinality {
+        chain_id,
+        finality_delay: 600,
+    };
+    bank.process(&program_id, &[config, admin, chain, system_program::id()], &finality)
+        .unwrap();
+    assert_eq!(bank.get(&chain).data().len(), ChainConfig::space());
+    let entry: ChainConfig = bank.get(&chain).state();
+    assert_eq!((entry.chain_id, entry.enabled, entry.finality_delay), (chain_id, true, 600));
+}
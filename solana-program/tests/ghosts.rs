@@ -0,0 +1,474 @@
+use borsh::BorshSerialize;
+use ghost_wallet_solana::test_support::{self, AccountFixture, Bank};
+use ghost_wallet_solana::{
+    account_version, client, ChainConfig, ChainFamily, GhostAccount, GhostError, GhostInstruction, GhostPayload,
+    GhostState, ACCOUNT_VERSION,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_program};
+
+#[test]
+fn delivery_uses_the_fee_fixed_at_creation() {
+    let mut config = test_support::config(Pubkey::new_unique(), &[], 1, 4);
+    config.fee_bps = 30;
+    let ghost = GhostAccount {
+        fee: Some(config.fee_for(1_000_000)),
+        ..test_support::ghost([1u8; 32], Pubkey::new_unique(), GhostState::Burned, 1_000_000)
+    };
+
+    config.fee_bps = 500;
+    assert_eq!(ghost.settlement_fee(&config), 3_000);
+    assert_eq!(ghost.delivered_amount(&config), 997_000);
+
+    // Ghosts from before fees were fixed pay whatever is configured now
+    let legacy = GhostAccount { fee: None, ..ghost };
+    assert_eq!(legacy.delivered_amount(&config), 950_000);
+}
+
+/// Ghost bytes as the program wrote them before accounts were versioned
+fn legacy_ghost_account(program_id: &Pubkey, key: Pubkey, ghost: &GhostAccount) -> AccountFixture {
+    const LEGACY_GHOST_SPACE: usize = 32 + 32 + 32 + 32 + 8 + 64 + 1 + 8 + 8 + 8 + 8 + 32 + 32 + 1 + 1;
+    let mut fixture = AccountFixture::new(key, *program_id, LEGACY_GHOST_SPACE);
+    let bytes = ghost.try_to_vec().unwrap();
+    fixture.data_mut().copy_from_slice(&bytes[..LEGACY_GHOST_SPACE]);
+    fixture
+}
+
+#[test]
+fn legacy_ghosts_grow_into_the_current_layout() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let validator = Pubkey::new_unique();
+    let mut bank = Bank::default();
+    let config = test_support::config(Pubkey::new_unique(), &[validator], 1, 4);
+    let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+    bank.add(AccountFixture::signer(validator, 1_000_000_000));
+
+    // A ghost id starting with ACCOUNT_VERSION must not pass for a header
+    let ghost_id = [ACCOUNT_VERSION; 32];
+    let ghost = test_support::ghost(ghost_id, Pubkey::new_unique(), GhostState::Created, 1_000_000);
+    let ghost = bank.add(legacy_ghost_account(&program_id, Pubkey::new_unique(), &ghost));
+
+    let lock = GhostInstruction::LockGhost { ghost_id };
+    assert_eq!(
+        bank.process(&program_id, &[config, ghost, validator], &lock),
+        Err(GhostError::InvalidSystemProgram.into())
+    );
+
+    bank.add(AccountFixture::readonly(system_program::id()));
+    bank.process(&program_id, &[config, ghost, validator, system_program::id()], &lock)
+        .unwrap();
+    let upgraded = bank.get(&ghost);
+    assert_eq!(account_version(upgraded.data()), Some(ACCOUNT_VERSION));
+    assert!(upgraded.lamports >= Rent::default().minimum_balance(upgraded.data().len()));
+    let locked: GhostAccount = upgraded.state();
+    assert_eq!((locked.ghost_id, locked.state, locked.amount), (ghost_id, GhostState::Locked, 1_000_000));
+}
+
+/// Config, a registered source chain and validators for mirrored ghosts
+/// paying out to `recipient`
+struct MirrorFixture {
+    program_id: Pubkey,
+    bank: Bank,
+    config: Pubkey,
+    admin: Pubkey,
+    validators: Vec<Pubkey>,
+    chain: Pubkey,
+    recipient: Pubkey,
+}
+
+impl MirrorFixture {
+    const SOURCE_CHAIN: u64 = 1;
+
+    fn new(validators: usize, finality_delay: i64) -> Self {
+        let program_id = Pubkey::new_unique();
+        let admin = Pubkey::new_unique();
+        let validators: Vec<Pubkey> = (0..validators).map(|_| Pubkey::new_unique()).collect();
+        let mut bank = Bank::default();
+        let config = test_support::config(admin, &validators, 1, 4);
+        let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+        bank.add(AccountFixture::signer(admin, 1_000_000_000));
+        for validator in &validators {
+            bank.add(AccountFixture::signer(*validator, 1_000_000_000));
+        }
+        let (chain, _) = Pubkey::find_program_address(&[b"chain", &Self::SOURCE_CHAIN.to_le_bytes()], &program_id);
+        let entry = ChainConfig {
+            chain_id: Self::SOURCE_CHAIN,
+            enabled: true,
+            finality_delay,
+            ..ChainConfig::default()
+        };
+        bank.add(test_support::borsh_account(&program_id, chain, ChainConfig::space(), &entry));
+        MirrorFixture {
+            program_id,
+            bank,
+            config,
+            admin,
+            validators,
+            chain,
+            recipient: Pubkey::new_unique(),
+        }
+    }
+
+    /// Ghost mirrored from the source chain in Burned, paying out to
+    /// `recipient`
+    fn burned(&self, ghost_id: [u8; 32]) -> GhostAccount {
+        let mut ghost = GhostAccount {
+            is_remote: true,
+            destination_chain: Self::SOURCE_CHAIN,
+            burn_proof: [5u8; 32],
+            fee: Some(0),
+            ..test_support::ghost(ghost_id, Pubkey::default(), GhostState::Burned, 1_000_000)
+        };
+        ghost.destination_address[..32].copy_from_slice(self.recipient.as_ref());
+        ghost
+    }
+
+    fn add_ghost(&mut self, ghost: &GhostAccount) -> Pubkey {
+        let fixture = test_support::ghost_account(&self.program_id, Pubkey::new_unique(), ghost);
+        self.bank.add(fixture)
+    }
+
+    /// Mints `ghost` to `recipient`, signed by the first validator and
+    /// `co_signers`
+    fn mint(&mut self, ghost: Pubkey, recipient: Pubkey, co_signers: &[Pubkey]) -> Result<(), ProgramError> {
+        let ghost_id = self.bank.get(&ghost).state::<GhostAccount>().ghost_id;
+        let mut keys = vec![self.config, ghost, self.validators[0], self.chain];
+        keys.extend_from_slice(co_signers);
+        let instruction = GhostInstruction::MintGhost {
+            ghost_id,
+            mint_proof: [6u8; 32],
+            recipient,
+        };
+        self.bank.process(&self.program_id, &keys, &instruction)
+    }
+}
+
+#[test]
+fn mirrored_ghosts_mint_only_to_the_attested_recipient() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = MirrorFixture::new(1, 0);
+    let ghost = fixture.add_ghost(&fixture.burned([4u8; 32]));
+
+    assert_eq!(
+        fixture.mint(ghost, Pubkey::new_unique(), &[]),
+        Err(GhostError::RecipientMismatch.into())
+    );
+    fixture.mint(ghost, fixture.recipient, &[]).unwrap();
+    assert_eq!(fixture.bank.get(&ghost).state::<GhostAccount>().state, GhostState::Minted);
+}
+
+#[test]
+fn vaa_payloads_must_carry_a_recipient() {
+    let recipient = Pubkey::new_unique();
+    let mut payload = vec![7u8; 32];
+    payload.extend_from_slice(&1_000u64.to_be_bytes());
+    payload.extend_from_slice(&[1u8; 64]);
+    payload.extend_from_slice(&900u64.to_be_bytes());
+    assert_eq!(GhostPayload::parse(&payload), None);
+
+    payload.extend_from_slice(recipient.as_ref());
+    let parsed = GhostPayload::parse(&payload).unwrap();
+    assert_eq!((parsed.amount, parsed.min_out, parsed.recipient), (1_000, 900, recipient));
+}
+
+#[test]
+fn mints_wait_for_the_source_burn_to_finalize() {
+    let sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = MirrorFixture::new(1, 300);
+
+    // Burned at 900 on the source chain, mirrored at 1_000
+    let mirrored = GhostAccount {
+        burn_ts: 1_000,
+        source_ts: 900,
+        ..fixture.burned([4u8; 32])
+    };
+    let ghost = fixture.add_ghost(&mirrored);
+
+    sysvars.set_time(1_199);
+    assert_eq!(fixture.mint(ghost, fixture.recipient, &[]), Err(GhostError::TooEarly.into()));
+    sysvars.set_time(1_200);
+    fixture.mint(ghost, fixture.recipient, &[]).unwrap();
+}
+
+/// Wormhole `PostedVAA` account carrying a ghost transfer payload
+fn posted_vaa(wormhole: &Pubkey, sequence: u64, ghost_id: [u8; 32], recipient: &Pubkey) -> AccountFixture {
+    let mut payload = ghost_id.to_vec();
+    payload.extend_from_slice(&1_000u64.to_be_bytes());
+    payload.extend_from_slice(&[1u8; 64]);
+    payload.extend_from_slice(&900u64.to_be_bytes());
+    payload.extend_from_slice(recipient.as_ref());
+
+    let mut data = b"vaa".to_vec();
+    data.extend_from_slice(&[1, 1]);
+    data.extend_from_slice(&900u32.to_le_bytes());
+    data.extend_from_slice(&[0u8; 32 + 4 + 4]);
+    data.extend_from_slice(&sequence.to_le_bytes());
+    data.extend_from_slice(&2u16.to_le_bytes());
+    data.extend_from_slice(&[9u8; 32]);
+    payload.serialize(&mut data).unwrap();
+
+    let mut fixture = AccountFixture::new(Pubkey::new_unique(), *wormhole, data.len());
+    fixture.data_mut().copy_from_slice(&data);
+    fixture
+}
+
+#[test]
+fn vaa_sequences_are_claimed_once_in_any_order() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let validator = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let wormhole = Pubkey::new_unique();
+    let mut bank = Bank::default();
+    let mut config = test_support::config(Pubkey::new_unique(), &[validator], 1, 4);
+    config.wormhole_program = wormhole;
+    config.vaa_emitter_chain = 2;
+    config.vaa_emitter_address = [9u8; 32];
+    config.vaa_source_chain = 1;
+    let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+    bank.add(AccountFixture::signer(validator, 1_000_000_000));
+    bank.add(AccountFixture::readonly(system_program::id()));
+
+    let claim = |sequence: u64| {
+        Pubkey::find_program_address(
+            &[b"vaa", &2u16.to_le_bytes(), &[9u8; 32], &sequence.to_le_bytes()],
+            &program_id,
+        )
+        .0
+    };
+    for sequence in [3, 5, 7] {
+        bank.add(AccountFixture::unallocated(claim(sequence)));
+    }
+
+    let mirror = |bank: &mut Bank, sequence: u64, ghost_id: [u8; 32], ghost: Option<Pubkey>| {
+        let ghost = ghost.unwrap_or_else(|| {
+            bank.add(AccountFixture::new(Pubkey::new_unique(), program_id, GhostAccount::space()))
+        });
+        let vaa = bank.add(posted_vaa(&wormhole, sequence, ghost_id, &recipient));
+        let claim = claim(sequence);
+        let keys = [config, ghost, validator, vaa, claim, system_program::id()];
+        let result = bank.process(&program_id, &keys, &GhostInstruction::MirrorGhostFromVaa { vaa_account: vaa });
+        (result, ghost, keys)
+    };
+
+    // A later sequence may land before an earlier one
+    let (result, first, keys) = mirror(&mut bank, 5, [1u8; 32], None);
+    result.unwrap();
+    mirror(&mut bank, 3, [2u8; 32], None).0.unwrap();
+    let mirrored: GhostAccount = bank.get(&first).state();
+    assert_eq!((mirrored.is_remote, mirrored.destination_chain, mirrored.source_ts), (true, 1, 900));
+
+    // Resubmitting the same VAA is a no-op; another VAA can't reuse its
+    // sequence or overwrite its ghost
+    bank.process(&program_id, &keys, &GhostInstruction::MirrorGhostFromVaa { vaa_account: keys[3] })
+        .unwrap();
+    assert_eq!(mirror(&mut bank, 5, [3u8; 32], None).0, Err(GhostError::VaaReplayed.into()));
+    assert_eq!(mirror(&mut bank, 7, [1u8; 32], Some(first)).0, Err(GhostError::GhostExists.into()));
+}
+
+#[test]
+fn destinations_must_match_the_chain_address_family() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let initiator = Pubkey::new_unique();
+    let mut bank = Bank::default();
+    let config = test_support::config(admin, &[], 1, 4);
+    let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+    bank.add(AccountFixture::signer(admin, 1_000_000_000));
+    bank.add(AccountFixture::signer(initiator, 1_000_000_000));
+    bank.add(AccountFixture::readonly(system_program::id()));
+
+    // Chain ids say nothing about address formats, so an entry without a
+    // family accepts no destination
+    let chain_id = 56u64;
+    let (chain, _) = Pubkey::find_program_address(&[b"chain", &chain_id.to_le_bytes()], &program_id);
+    let entry = ChainConfig {
+        chain_id,
+        enabled: true,
+        ..ChainConfig::default()
+    };
+    bank.add(test_support::borsh_account(&program_id, chain, ChainConfig::space(), &entry));
+    let ghost_id = [8u8; 32];
+    let mut created = test_support::ghost(ghost_id, initiator, GhostState::Created, 1_000);
+    created.destination_chain = chain_id;
+    let ghost = bank.add(test_support::ghost_account(&program_id, Pubkey::new_unique(), &created));
+
+    let evm = client::encode_destination(ChainFamily::Evm, &[0xAB; 20]).unwrap();
+    let update = |destination_address| GhostInstruction::UpdateDestination {
+        ghost_id,
+        destination_address,
+        destination_token: Pubkey::default(),
+    };
+    let keys = [config, ghost, initiator, chain];
+    assert_eq!(bank.process(&program_id, &keys, &update(evm)), Err(GhostError::InvalidDestination.into()));
+
+    let family = GhostInstruction::SetChainAddressFamily {
+        chain_id,
+        family: ChainFamily::Evm,
+    };
+    bank.process(&program_id, &[config, admin, chain, system_program::id()], &family)
+        .unwrap();
+    let solana = client::encode_destination(ChainFamily::Solana, &[0xCD; 32]).unwrap();
+    assert_eq!(bank.process(&program_id, &keys, &update(solana)), Err(GhostError::InvalidDestination.into()));
+    bank.process(&program_id, &keys, &update(evm)).unwrap();
+    assert_eq!(bank.get(&ghost).state::<GhostAccount>().destination_address, evm);
+    assert_eq!(client::encode_destination(ChainFamily::Evm, &[0xAB; 32]), None);
+}
+
+#[test]
+fn closing_a_remote_ghost_takes_the_validator_threshold() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let validators = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut bank = Bank::default();
+    let config = test_support::config(Pubkey::new_unique(), &validators, 2, 4);
+    let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+    for validator in validators {
+        bank.add(AccountFixture::signer(validator, 1_000_000_000));
+    }
+    let rent_destination = bank.add(AccountFixture::signer(Pubkey::new_unique(), 0));
+
+    let ghost_id = [9u8; 32];
+    let settled = GhostAccount {
+        is_remote: true,
+        ..test_support::ghost(ghost_id, Pubkey::default(), GhostState::Settled, 1_000)
+    };
+    let ghost = bank.add(test_support::ghost_account(&program_id, Pubkey::new_unique(), &settled));
+    let rent = bank.get(&ghost).lamports;
+
+    let close = GhostInstruction::CloseGhost { ghost_id };
+    assert_eq!(
+        bank.process(&program_id, &[config, ghost, validators[0], rent_destination], &close),
+        Err(GhostError::ThresholdNotMet.into())
+    );
+    bank.process(&program_id, &[config, ghost, validators[0], rent_destination, validators[1]], &close)
+        .unwrap();
+    assert_eq!(bank.get(&rent_destination).lamports, rent);
+}
+
+#[test]
+fn burned_ghosts_keep_their_threshold_through_a_bump() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = MirrorFixture::new(2, 0);
+
+    // One ghost burned under a threshold of one, one from before thresholds
+    // were recorded
+    let burned = |fixture: &mut MirrorFixture, ghost_id: [u8; 32], required_signers: u8| {
+        let ghost = GhostAccount {
+            burn_ts: 900,
+            source_ts: 900,
+            required_signers,
+            ..fixture.burned(ghost_id)
+        };
+        fixture.add_ghost(&ghost)
+    };
+    let recorded = burned(&mut fixture, [1u8; 32], 1);
+    let legacy = burned(&mut fixture, [2u8; 32], 0);
+
+    let bump = GhostInstruction::BatchConfigure {
+        validators: Vec::new(),
+        threshold: Some(2),
+        fee_bps: None,
+    };
+    let (config, admin) = (fixture.config, fixture.admin);
+    fixture.bank.process(&fixture.program_id, &[config, admin], &bump).unwrap();
+
+    let recipient = fixture.recipient;
+    fixture.mint(recorded, recipient, &[]).unwrap();
+    assert_eq!(fixture.bank.get(&recorded).state::<GhostAccount>().state, GhostState::Minted);
+
+    assert_eq!(fixture.mint(legacy, recipient, &[]), Err(GhostError::ThresholdNotMet.into()));
+    let co_signer = fixture.validators[1];
+    fixture.mint(legacy, recipient, &[co_signer]).unwrap();
+}
+
+#[test]
+fn creating_a_ghost_never_overwrites_another() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let victim = Pubkey::new_unique();
+    let attacker = Pubkey::new_unique();
+    let mut bank = Bank::default();
+    let config = test_support::config(Pubkey::new_unique(), &[], 1, 4);
+    let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+    bank.add(AccountFixture::signer(attacker, 1_000_000_000));
+    bank.add(AccountFixture::readonly(system_program::id()));
+    let in_flight = test_support::ghost([1u8; 32], victim, GhostState::Locked, 1_000);
+    let ghost = bank.add(test_support::ghost_account(&program_id, Pubkey::new_unique(), &in_flight));
+
+    let ghost_id = [2u8; 32];
+    let (vault, _) = Pubkey::find_program_address(&[b"vault", &ghost_id], &program_id);
+    bank.add(AccountFixture::unallocated(vault));
+    let chain_id = 56u64;
+    let (chain, _) = Pubkey::find_program_address(&[b"chain", &chain_id.to_le_bytes()], &program_id);
+    let entry = ChainConfig {
+        chain_id,
+        enabled: true,
+        address_family: Some(ChainFamily::Evm),
+        ..ChainConfig::default()
+    };
+    bank.add(test_support::borsh_account(&program_id, chain, ChainConfig::space(), &entry));
+
+    let create = GhostInstruction::CreateGhost {
+        ghost_id,
+        amount: 1_000,
+        destination_chain: chain_id,
+        destination_address: client::encode_destination(ChainFamily::Evm, &[0xAB; 20]).unwrap(),
+        source_token: Pubkey::default(),
+        destination_token: Pubkey::default(),
+        min_out: 0,
+        verify_id: false,
+        nonce: 0,
+    };
+    let keys = [config, ghost, attacker, attacker, system_program::id(), vault, chain];
+    assert_eq!(bank.process(&program_id, &keys, &create), Err(GhostError::GhostExists.into()));
+    let kept: GhostAccount = bank.get(&ghost).state();
+    assert_eq!((kept.ghost_id, kept.initiator, kept.state), ([1u8; 32], victim, GhostState::Locked));
+}
+
+#[test]
+fn local_ghosts_settle_on_the_remote_ack_not_a_local_mint() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let program_id = Pubkey::new_unique();
+    let validator = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let mut bank = Bank::default();
+    let mut config = test_support::config(Pubkey::new_unique(), &[validator], 1, 4);
+    config.treasury = treasury;
+    let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+    bank.add(AccountFixture::signer(validator, 1_000_000_000));
+    let treasury = bank.add(AccountFixture::signer(treasury, 0));
+
+    let local = |ghost_id: [u8; 32], state: GhostState| {
+        let mut ghost = GhostAccount {
+            burn_proof: [5u8; 32],
+            burn_ts: 900,
+            fee: Some(0),
+            ..test_support::ghost(ghost_id, Pubkey::new_unique(), state, 1_000)
+        };
+        ghost.destination_address[..32].copy_from_slice(recipient.as_ref());
+        let mut fixture = test_support::ghost_account(&program_id, Pubkey::new_unique(), &ghost);
+        fixture.lamports += 1_000;
+        fixture
+    };
+    let burned = bank.add(local([1u8; 32], GhostState::Burned));
+    let mint = GhostInstruction::MintGhost {
+        ghost_id: [1u8; 32],
+        mint_proof: [6u8; 32],
+        recipient,
+    };
+    assert_eq!(
+        bank.process(&program_id, &[config, burned, validator], &mint),
+        Err(GhostError::InvalidState.into())
+    );
+
+    // Local ghosts minted here before that was refused can still settle
+    let minted = bank.add(local([2u8; 32], GhostState::Minted));
+    let destroy = GhostInstruction::DestroyGhost { ghost_id: [2u8; 32] };
+    bank.process(&program_id, &[config, minted, validator, treasury], &destroy)
+        .unwrap();
+    assert_eq!(bank.get(&minted).state::<GhostAccount>().state, GhostState::Settled);
+    assert_eq!(bank.get(&treasury).lamports, 1_000);
+}
@@ -0,0 +1,75 @@
+use borsh::BorshSerialize;
+use ghost_wallet_solana::{GhostError, GhostInstruction, INSTRUCTION_PREFIX};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+#[test]
+fn current_instructions_round_trip() {
+    let deposit = GhostInstruction::DepositToPool {
+        amount: 5_000,
+        min_shares_out: 4_900,
+    };
+    match GhostInstruction::unpack(&deposit.pack()).unwrap() {
+        GhostInstruction::DepositToPool { amount, min_shares_out } => {
+            assert_eq!((amount, min_shares_out), (5_000, 4_900))
+        }
+        other => panic!("decoded {:?}", other),
+    }
+    let unknown = [INSTRUCTION_PREFIX, 9, 0];
+    assert_eq!(
+        GhostInstruction::unpack(&unknown).map(|_| ()),
+        Err(ProgramError::from(GhostError::InvalidInstruction))
+    );
+}
+
+#[test]
+fn unprefixed_launch_encodings_still_decode() {
+    let ghost_id = [3u8; 32];
+    let source_token = Pubkey::new_unique();
+    let mut data = vec![2u8];
+    (ghost_id, 1_000u64, 2u64, [4u8; 64], source_token, Pubkey::new_unique())
+        .serialize(&mut data)
+        .unwrap();
+    match GhostInstruction::unpack(&data).unwrap() {
+        GhostInstruction::CreateGhost { ghost_id: id, amount, min_out, verify_id, nonce, source_token: token, .. } => {
+            assert_eq!((id, amount, token), (ghost_id, 1_000, source_token));
+            assert_eq!((min_out, verify_id, nonce), (0, false, 0));
+        }
+        other => panic!("decoded {:?}", other),
+    }
+
+    // Launch DepositToPool had no slippage floor
+    let mut data = vec![10u8];
+    7_000u64.serialize(&mut data).unwrap();
+    assert!(matches!(
+        GhostInstruction::unpack(&data).unwrap(),
+        GhostInstruction::DepositToPool { amount: 7_000, min_shares_out: 0 }
+    ));
+
+    // An ack without its source chain can't be checked, so it is refused
+    let mut data = vec![7u8];
+    ghost_id.serialize(&mut data).unwrap();
+    assert_eq!(
+        GhostInstruction::unpack(&data).map(|_| ()),
+        Err(ProgramError::from(GhostError::InvalidInstruction))
+    );
+}
+
+#[test]
+fn version_one_encodings_decode_with_old_defaults() {
+    let mut data = vec![INSTRUCTION_PREFIX, 1, 36];
+    (1_000u64, 50_000u64).serialize(&mut data).unwrap();
+    assert!(matches!(
+        GhostInstruction::unpack(&data).unwrap(),
+        GhostInstruction::SetPoolLimits { min_deposit: 1_000, max_pool_size: 50_000, min_withdrawal: 0 }
+    ));
+
+    // Version 1 mirrors carry no recipient for the mint to pay
+    let mut data = vec![INSTRUCTION_PREFIX, 1, 5];
+    ([1u8; 32], 1u64, 1_000u64, [2u8; 32], Pubkey::new_unique(), Pubkey::new_unique(), 0u64)
+        .serialize(&mut data)
+        .unwrap();
+    assert_eq!(
+        GhostInstruction::unpack(&data).map(|_| ()),
+        Err(ProgramError::from(GhostError::InvalidInstruction))
+    );
+}
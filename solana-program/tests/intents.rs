@@ -0,0 +1,298 @@
+use ghost_wallet_solana::test_support::{self, AccountFixture, Bank};
+use ghost_wallet_solana::{
+    ClosedIntent, GhostError, GhostInstruction, LiquidityPool, PaymentIntent, PoolRegistry,
+    ProgramConfig,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_program};
+
+const LAMPORTS: u64 = 1_000_000_000;
+const SENDER_CHAIN: u64 = 1;
+
+/// A funded pool registered for `dest_token` and a relayer that is the
+/// only validator
+struct IntentFixture {
+    program_id: Pubkey,
+    bank: Bank,
+    config: Pubkey,
+    pool: Pubkey,
+    registry: Pubkey,
+    chain: Pubkey,
+    relayer: Pubkey,
+    recipient: Pubkey,
+    dest_token: Pubkey,
+}
+
+impl IntentFixture {
+    fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let relayer = Pubkey::new_unique();
+        let dest_token = Pubkey::new_unique();
+        let mut bank = Bank::default();
+
+        let config = test_support::config(Pubkey::new_unique(), &[relayer], 1, 4);
+        let config = bank.add(test_support::config_account(&program_id, Pubkey::new_unique(), &config));
+        let seed = [7u8; 32];
+        let pool = test_support::pool(seed);
+        let pool = LiquidityPool {
+            total_deposited: 10 * LAMPORTS,
+            total_shares: 10 * LAMPORTS,
+            available_liquidity: 10 * LAMPORTS,
+            ..pool
+        };
+        let pool = bank.add(test_support::pool_account(&program_id, Pubkey::new_unique(), &pool));
+        let (registry, _) =
+            Pubkey::find_program_address(&[b"pool_registry", dest_token.as_ref()], &program_id);
+        let entry = PoolRegistry {
+            dest_token,
+            pool_seed: seed,
+            pool,
+        };
+        bank.add(test_support::borsh_account(&program_id, registry, PoolRegistry::space(), &entry));
+        let (chain, _) =
+            Pubkey::find_program_address(&[b"chain", &SENDER_CHAIN.to_le_bytes()], &program_id);
+        bank.add(AccountFixture::unallocated(chain));
+        bank.add(AccountFixture::signer(relayer, 10 * LAMPORTS));
+        bank.add(AccountFixture::readonly(system_program::id()));
+        let recipient = bank.add(AccountFixture::unallocated(Pubkey::new_unique()));
+
+        IntentFixture {
+            program_id,
+            bank,
+            config,
+            pool,
+            registry,
+            chain,
+            relayer,
+            recipient,
+            dest_token,
+        }
+    }
+
+    fn update_config(&mut self, update: impl FnOnce(&mut ProgramConfig)) {
+        let mut config = test_support::config_state(&self.program_id, self.bank.get_mut(&self.config));
+        update(&mut config);
+        self.bank.add(test_support::config_account(&self.program_id, self.config, &config));
+    }
+
+    fn open_intents(&mut self) -> u32 {
+        test_support::config_state(&self.program_id, self.bank.get_mut(&self.config)).open_intents
+    }
+
+    /// The unallocated PDA RecordPaymentIntent creates for `intent_id`
+    fn intent_account(&mut self, intent_id: [u8; 32]) -> Pubkey {
+        let (intent, _) = Pubkey::find_program_address(&[b"intent", &intent_id], &self.program_id);
+        self.bank.add(AccountFixture::unallocated(intent))
+    }
+
+    fn record(&mut self, intent: Pubkey, intent_id: [u8; 32], amount: u64) -> Result<(), ProgramError> {
+        let instruction = GhostInstruction::RecordPaymentIntent {
+            intent_id,
+            sender_chain: SENDER_CHAIN,
+            sender_address: [1u8; 64],
+            amount,
+            dest_token: self.dest_token,
+        };
+        let keys = [self.config, intent, self.relayer, self.registry, self.pool, system_program::id()];
+        self.bank.process(&self.program_id, &keys, &instruction)
+    }
+
+    fn cancel(&mut self, intent: Pubkey, intent_id: [u8; 32]) -> Result<(), ProgramError> {
+        let keys = [self.config, intent, self.relayer];
+        self.bank
+            .process(&self.program_id, &keys, &GhostInstruction::CancelIntent { intent_id })
+    }
+
+    fn close(&mut self, intent: Pubkey, intent_id: [u8; 32]) -> Result<(), ProgramError> {
+        let keys = [self.config, intent, self.relayer];
+        self.bank
+            .process(&self.program_id, &keys, &GhostInstruction::CloseIntent { intent_id })
+    }
+
+    fn execute(&mut self, intent: Pubkey, intent_id: [u8; 32], amount: u64) -> Result<(), ProgramError> {
+        let instruction = GhostInstruction::ExecutePayment {
+            intent_id,
+            recipient: self.recipient,
+            amount,
+        };
+        let keys = [self.config, self.pool, self.recipient, self.relayer, intent, self.registry, self.chain];
+        self.bank.process(&self.program_id, &keys, &instruction)
+    }
+}
+
+#[test]
+fn recording_over_a_partly_paid_intent_is_rejected() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    let intent_id = [3u8; 32];
+    let intent = fixture.intent_account(intent_id);
+
+    fixture.record(intent, intent_id, LAMPORTS).unwrap();
+    fixture.execute(intent, intent_id, LAMPORTS / 2).unwrap();
+
+    assert_eq!(
+        fixture.record(intent, intent_id, LAMPORTS),
+        Err(GhostError::InvalidState.into())
+    );
+    let recorded: PaymentIntent = fixture.bank.get(&intent).state();
+    assert_eq!(recorded.amount_paid, LAMPORTS / 2);
+
+    // Only the unpaid half can still go out
+    assert_eq!(
+        fixture.execute(intent, intent_id, LAMPORTS),
+        Err(GhostError::Overpayment.into())
+    );
+    fixture.execute(intent, intent_id, LAMPORTS / 2).unwrap();
+    assert_eq!(fixture.bank.get(&fixture.recipient).lamports, LAMPORTS);
+}
+
+#[test]
+fn finished_intents_free_their_open_slot_once() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    fixture.update_config(|config| config.max_open_intents = 1);
+    let (first, second) = (fixture.intent_account([1u8; 32]), fixture.intent_account([2u8; 32]));
+
+    fixture.record(first, [1u8; 32], LAMPORTS).unwrap();
+    assert_eq!(
+        fixture.record(second, [2u8; 32], LAMPORTS),
+        Err(GhostError::TooManyIntents.into())
+    );
+    fixture.cancel(first, [1u8; 32]).unwrap();
+    assert_eq!(fixture.open_intents(), 0);
+
+    fixture.record(second, [2u8; 32], LAMPORTS).unwrap();
+    fixture.execute(second, [2u8; 32], LAMPORTS).unwrap();
+    assert_eq!(fixture.open_intents(), 0);
+    assert!(!fixture.bank.get(&second).state::<PaymentIntent>().counted);
+}
+
+#[test]
+fn uncounted_intents_leave_the_open_count_alone() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    let counted = fixture.intent_account([1u8; 32]);
+    fixture.record(counted, [1u8; 32], LAMPORTS).unwrap();
+
+    // Recorded, at an address of the relayer's choosing, before open intents
+    // were counted
+    let legacy = Pubkey::new_unique();
+    let intent = PaymentIntent {
+        counted: false,
+        intent_id: [2u8; 32],
+        ..fixture.bank.get(&counted).state()
+    };
+    let program_id = fixture.program_id;
+    fixture
+        .bank
+        .add(test_support::borsh_account(&program_id, legacy, PaymentIntent::space(), &intent));
+
+    fixture.execute(legacy, [2u8; 32], LAMPORTS).unwrap();
+    assert_eq!(fixture.open_intents(), 1);
+    fixture.cancel(counted, [1u8; 32]).unwrap();
+    assert_eq!(fixture.open_intents(), 0);
+}
+
+#[test]
+fn closed_intents_cannot_be_recorded_again() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    let intent_id = [4u8; 32];
+    let intent = fixture.intent_account(intent_id);
+    let relayer_lamports = fixture.bank.get(&fixture.relayer).lamports;
+
+    fixture.record(intent, intent_id, LAMPORTS).unwrap();
+    assert_eq!(fixture.close(intent, intent_id), Err(GhostError::InvalidState.into()));
+    fixture.execute(intent, intent_id, LAMPORTS).unwrap();
+    fixture.close(intent, intent_id).unwrap();
+
+    // Everything but the closed record's rent is back with the relayer
+    let closed = fixture.bank.get(&intent);
+    assert_eq!(closed.data().len(), ClosedIntent::space());
+    assert_eq!(closed.state::<ClosedIntent>().intent_id, intent_id);
+    assert_eq!(
+        fixture.bank.get(&fixture.relayer).lamports,
+        relayer_lamports - Rent::default().minimum_balance(ClosedIntent::space())
+    );
+
+    assert_eq!(
+        fixture.record(intent, intent_id, LAMPORTS),
+        Err(GhostError::InvalidState.into())
+    );
+    assert!(fixture.execute(intent, intent_id, LAMPORTS).is_err());
+    assert_eq!(fixture.bank.get(&fixture.recipient).lamports, LAMPORTS);
+}
+
+#[test]
+fn intents_live_at_their_pda() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    let elsewhere = fixture.intent_account([5u8; 32]);
+    assert_eq!(
+        fixture.record(elsewhere, [6u8; 32], LAMPORTS),
+        Err(ProgramError::InvalidSeeds)
+    );
+}
+
+#[test]
+fn a_prefunded_intent_address_can_still_be_recorded() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    let intent_id = [8u8; 32];
+    let intent = fixture.intent_account(intent_id);
+    // Anyone can send lamports to the PDA before the relayer records it
+    fixture.bank.get_mut(&intent).lamports = 1;
+    let relayer_lamports = fixture.bank.get(&fixture.relayer).lamports;
+
+    fixture.record(intent, intent_id, LAMPORTS).unwrap();
+    let recorded = fixture.bank.get(&intent);
+    let rent = Rent::default().minimum_balance(PaymentIntent::space());
+    assert_eq!(recorded.owner, fixture.program_id);
+    assert_eq!(recorded.lamports, rent);
+    assert_eq!(recorded.state::<PaymentIntent>().intent_id, intent_id);
+    assert_eq!(fixture.bank.get(&fixture.relayer).lamports, relayer_lamports - (rent - 1));
+}
+
+#[test]
+fn payout_fees_accrue_to_the_pool_lps() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    fixture.update_config(|config| config.fee_bps = 100);
+    // Fees a pool kept before they were credited
+    let mut pool = fixture.bank.get(&fixture.pool).pool();
+    pool.total_fees = 1_000;
+    let lamports = fixture.bank.get(&fixture.pool).lamports + 1_000;
+    let mut account = test_support::pool_account(&fixture.program_id, fixture.pool, &pool);
+    account.lamports = lamports;
+    fixture.bank.add(account);
+
+    let intent_id = [6u8; 32];
+    let intent = fixture.intent_account(intent_id);
+    fixture.record(intent, intent_id, LAMPORTS).unwrap();
+    fixture.execute(intent, intent_id, LAMPORTS).unwrap();
+
+    let fee = LAMPORTS / 100;
+    assert_eq!(fixture.bank.get(&fixture.recipient).lamports, LAMPORTS - fee);
+    let pool = fixture.bank.get(&fixture.pool).pool();
+    assert_eq!(pool.total_fees, 0);
+    assert_eq!(pool.total_deposited, 10 * LAMPORTS + fee + 1_000);
+    assert_eq!(pool.available_liquidity, 9 * LAMPORTS + fee + 1_000);
+    let rent = Rent::default().minimum_balance(fixture.bank.get(&fixture.pool).data().len());
+    assert_eq!(pool.check_invariants(fixture.bank.get(&fixture.pool).lamports, rent), Ok(()));
+    // Every share is now worth more than the lamport it was minted for
+    assert!(pool.shares_value(LAMPORTS) > LAMPORTS);
+}
+
+#[test]
+fn paused_programs_make_no_payouts() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = IntentFixture::new();
+    let intent_id = [7u8; 32];
+    let intent = fixture.intent_account(intent_id);
+    fixture.record(intent, intent_id, LAMPORTS).unwrap();
+
+    fixture.update_config(|config| config.paused = true);
+    assert_eq!(fixture.execute(intent, intent_id, LAMPORTS), Err(GhostError::Paused.into()));
+    fixture.update_config(|config| config.paused = false);
+    fixture.execute(intent, intent_id, LAMPORTS).unwrap();
+    assert_eq!(fixture.bank.get(&fixture.recipient).lamports, LAMPORTS);
+}
@@ -0,0 +1,291 @@
+use ghost_wallet_solana::test_support::{self, AccountFixture, Bank};
+use ghost_wallet_solana::{
+    position_value, AccountKind, GhostError, GhostInstruction, LPPosition, LiquidityPool,
+    ProgramConfig, MINIMUM_LIQUIDITY,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_program};
+
+const LAMPORTS: u64 = 1_000_000_000;
+
+/// One pool and an LP with a blank position account
+struct PoolFixture {
+    program_id: Pubkey,
+    bank: Bank,
+    pool: Pubkey,
+    position: Pubkey,
+    lp: Pubkey,
+}
+
+impl PoolFixture {
+    fn new(pool: &LiquidityPool) -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut bank = Bank::default();
+        let pool = bank.add(test_support::pool_account(&program_id, Pubkey::new_unique(), pool));
+        let position = bank.add(AccountFixture::new(Pubkey::new_unique(), program_id, LPPosition::space()));
+        let lp = bank.add(AccountFixture::signer(Pubkey::new_unique(), 10 * LAMPORTS));
+        bank.add(AccountFixture::readonly(system_program::id()));
+        PoolFixture {
+            program_id,
+            bank,
+            pool,
+            position,
+            lp,
+        }
+    }
+
+    /// Replaces the position account with one holding `position`
+    fn seed_position(&mut self, position: &LPPosition) {
+        let fixture = test_support::borsh_account(&self.program_id, self.position, LPPosition::space(), position);
+        self.bank.add(fixture);
+    }
+
+    fn deposit(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.deposit_at_least(amount, 0)
+    }
+
+    fn deposit_at_least(&mut self, amount: u64, min_shares_out: u64) -> Result<(), ProgramError> {
+        let keys = [self.pool, self.position, self.lp, system_program::id()];
+        let instruction = GhostInstruction::DepositToPool { amount, min_shares_out };
+        self.bank.process(&self.program_id, &keys, &instruction)
+    }
+
+    /// A second LP with a blank position in the same pool
+    fn add_lp(&mut self, lamports: u64) -> (Pubkey, Pubkey) {
+        let position = AccountFixture::new(Pubkey::new_unique(), self.program_id, LPPosition::space());
+        let position = self.bank.add(position);
+        let lp = self.bank.add(AccountFixture::signer(Pubkey::new_unique(), lamports));
+        (lp, position)
+    }
+
+    fn as_lp(&mut self, lp: Pubkey, position: Pubkey) {
+        (self.lp, self.position) = (lp, position);
+    }
+
+    fn position_value(&self) -> u64 {
+        let position: LPPosition = self.bank.get(&self.position).state();
+        position_value(&self.bank.get(&self.pool).pool(), &position)
+    }
+
+    fn withdraw(&mut self, shares: u64) -> Result<(), ProgramError> {
+        let keys = [self.pool, self.position, self.lp];
+        self.bank
+            .process(&self.program_id, &keys, &GhostInstruction::WithdrawFromPool { shares })
+    }
+}
+
+#[test]
+fn deposit_then_withdraw_round_trips() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = PoolFixture::new(&test_support::pool([1u8; 32]));
+    let pool_lamports = fixture.bank.get(&fixture.pool).lamports;
+
+    fixture.deposit(LAMPORTS).unwrap();
+    assert_eq!(fixture.bank.get(&fixture.pool).lamports, pool_lamports + LAMPORTS);
+    assert_eq!(fixture.bank.get(&fixture.lp).lamports, 9 * LAMPORTS);
+    let position: LPPosition = fixture.bank.get(&fixture.position).state();
+    let pool = fixture.bank.get(&fixture.pool).pool();
+    assert_eq!(position.shares, LAMPORTS - MINIMUM_LIQUIDITY);
+    assert_eq!(pool.total_shares, LAMPORTS);
+    assert_eq!(pool.total_deposited, LAMPORTS);
+
+    // The locked shares' lamports stay behind in the pool
+    fixture.withdraw(position.shares).unwrap();
+    assert_eq!(fixture.bank.get(&fixture.pool).lamports, pool_lamports + MINIMUM_LIQUIDITY);
+    assert_eq!(fixture.bank.get(&fixture.lp).lamports, 10 * LAMPORTS - MINIMUM_LIQUIDITY);
+    assert_eq!(fixture.bank.get(&fixture.position).state::<LPPosition>().shares, 0);
+}
+
+#[test]
+fn positions_only_move_in_their_own_pool() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let pool = LiquidityPool {
+        total_deposited: 4 * LAMPORTS,
+        total_shares: 4 * LAMPORTS,
+        available_liquidity: 4 * LAMPORTS,
+        ..test_support::pool([1u8; 32])
+    };
+    let mut fixture = PoolFixture::new(&pool);
+    // Shares minted by another pool, at a cheaper price
+    fixture.seed_position(&LPPosition {
+        owner: fixture.lp,
+        pool: [2u8; 32],
+        shares: 4 * LAMPORTS,
+        deposited_at: 0,
+    });
+
+    assert_eq!(fixture.withdraw(LAMPORTS), Err(ProgramError::InvalidAccountData));
+    assert_eq!(fixture.deposit(LAMPORTS), Err(ProgramError::InvalidAccountData));
+    assert_eq!(fixture.bank.get(&fixture.pool).pool().total_deposited, 4 * LAMPORTS);
+}
+
+#[test]
+fn positions_must_belong_to_the_lp_and_the_program() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = PoolFixture::new(&test_support::pool([1u8; 32]));
+    fixture.seed_position(&LPPosition {
+        owner: Pubkey::new_unique(),
+        pool: [1u8; 32],
+        shares: 0,
+        deposited_at: 0,
+    });
+    assert_eq!(fixture.deposit(LAMPORTS), Err(ProgramError::InvalidAccountData));
+
+    let foreign = AccountFixture::new(fixture.position, Pubkey::new_unique(), LPPosition::space());
+    fixture.bank.add(foreign);
+    assert_eq!(fixture.deposit(LAMPORTS), Err(GhostError::IncorrectProgramId.into()));
+    assert_eq!(fixture.withdraw(1), Err(GhostError::IncorrectProgramId.into()));
+}
+
+#[test]
+fn donation_after_a_dust_deposit_does_not_cheat_the_next_depositor() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = PoolFixture::new(&test_support::pool([1u8; 32]));
+    let victim = fixture.add_lp(100 * LAMPORTS);
+
+    // The attacker takes the first share as cheaply as the pool allows, then
+    // sends lamports straight to the pool to inflate its price
+    let attacker_start = fixture.bank.get(&fixture.lp).lamports;
+    fixture.deposit(MINIMUM_LIQUIDITY + 1).unwrap();
+    let donation = 5 * LAMPORTS;
+    fixture.bank.get_mut(&fixture.lp).lamports -= donation;
+    fixture.bank.get_mut(&fixture.pool).lamports += donation;
+    let attacker = (fixture.lp, fixture.position);
+
+    // A victim quoting at the pre-donation price is protected by its bound
+    fixture.as_lp(victim.0, victim.1);
+    let deposit = 20 * LAMPORTS;
+    assert_eq!(
+        fixture.deposit_at_least(deposit, deposit / 100 * 99),
+        Err(GhostError::SlippageExceeded.into())
+    );
+
+    // Depositing anyway loses at most one share's worth to rounding
+    fixture.deposit(deposit).unwrap();
+    let pool = fixture.bank.get(&fixture.pool).pool();
+    let share_price = pool.total_deposited / pool.total_shares;
+    assert!(fixture.position_value() + share_price >= deposit);
+
+    // The donation went mostly to the locked shares, so the attack loses money
+    fixture.as_lp(attacker.0, attacker.1);
+    let attacker_shares = fixture.bank.get(&fixture.position).state::<LPPosition>().shares;
+    fixture.withdraw(attacker_shares).unwrap();
+    assert!(fixture.bank.get(&fixture.lp).lamports < attacker_start - donation / 2);
+}
+
+#[test]
+fn sweeping_a_pool_leaves_donations_to_its_lps() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = PoolFixture::new(&test_support::pool([1u8; 32]));
+    fixture.deposit(LAMPORTS).unwrap();
+
+    // Over-allocate the pool by 512 bytes and donate to it
+    let mut oversized = fixture.bank.get(&fixture.pool).clone();
+    let pool = oversized.pool();
+    let mut grown = AccountFixture::new(fixture.pool, fixture.program_id, LiquidityPool::space() + 512);
+    grown.data_mut()[..LiquidityPool::space()].copy_from_slice(oversized.data_mut());
+    let freed = Rent::default().minimum_balance(LiquidityPool::space() + 512)
+        - Rent::default().minimum_balance(LiquidityPool::space());
+    let donation = LAMPORTS / 10;
+    grown.lamports = oversized.lamports + freed + donation;
+    fixture.bank.add(grown);
+
+    let admin = fixture.bank.add(AccountFixture::signer(Pubkey::new_unique(), LAMPORTS));
+    let treasury = fixture.bank.add(AccountFixture::unallocated(Pubkey::new_unique()));
+    let config = ProgramConfig {
+        treasury,
+        ..test_support::config(admin, &[], 1, 4)
+    };
+    let config = fixture
+        .bank
+        .add(test_support::config_account(&fixture.program_id, Pubkey::new_unique(), &config));
+
+    let keys = [config, fixture.pool, admin, treasury];
+    let sweep = GhostInstruction::SweepExcessRent { kind: AccountKind::Pool };
+    fixture.bank.process(&fixture.program_id, &keys, &sweep).unwrap();
+    assert_eq!(fixture.bank.get(&treasury).lamports, freed);
+    assert_eq!(fixture.bank.get(&fixture.pool).data().len(), LiquidityPool::space());
+
+    // The next deposit credits the donation to the existing LP
+    let (lp, position) = (fixture.lp, fixture.position);
+    let newcomer = fixture.add_lp(2 * LAMPORTS);
+    fixture.as_lp(newcomer.0, newcomer.1);
+    fixture.deposit(LAMPORTS).unwrap();
+    fixture.as_lp(lp, position);
+    let stake = pool.total_deposited - MINIMUM_LIQUIDITY;
+    assert!(fixture.position_value() > stake + donation / 100 * 99);
+}
+
+#[test]
+fn admin_setters_update_the_pool_in_place() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = PoolFixture::new(&test_support::pool([1u8; 32]));
+    fixture.deposit(LAMPORTS).unwrap();
+    let before = fixture.bank.get(&fixture.pool).pool();
+
+    let admin = fixture.bank.add(AccountFixture::signer(Pubkey::new_unique(), LAMPORTS));
+    let config = test_support::config(admin, &[], 1, 4);
+    let config = fixture
+        .bank
+        .add(test_support::config_account(&fixture.program_id, Pubkey::new_unique(), &config));
+    let keys = [config, admin, fixture.pool];
+    let limits = GhostInstruction::SetPoolLimits {
+        min_deposit: 10,
+        max_pool_size: 100 * LAMPORTS,
+        min_withdrawal: 5,
+    };
+    fixture.bank.process(&fixture.program_id, &keys, &limits).unwrap();
+
+    let after = fixture.bank.get(&fixture.pool).pool();
+    assert_eq!((after.min_deposit, after.max_pool_size, after.min_withdrawal), (10, 100 * LAMPORTS, 5));
+    assert_eq!(after.total_shares, before.total_shares);
+    assert_eq!(after.available_liquidity, before.available_liquidity);
+
+    let closed = LiquidityPool { closed: true, ..after };
+    fixture
+        .bank
+        .add(test_support::pool_account(&fixture.program_id, fixture.pool, &closed));
+    let activate = GhostInstruction::SetPoolActive { active: true };
+    assert_eq!(
+        fixture.bank.process(&fixture.program_id, &keys, &activate),
+        Err(GhostError::InvalidState.into())
+    );
+}
+
+#[test]
+fn a_pool_drains_once_its_lps_have_withdrawn() {
+    let _sysvars = test_support::install_sysvars(1_000);
+    let mut fixture = PoolFixture::new(&test_support::pool([1u8; 32]));
+    fixture.deposit(LAMPORTS).unwrap();
+
+    let admin = fixture.bank.add(AccountFixture::signer(Pubkey::new_unique(), LAMPORTS));
+    let destination = fixture.bank.add(AccountFixture::signer(Pubkey::new_unique(), 0));
+    let config = test_support::config(admin, &[], 1, 4);
+    let config = fixture
+        .bank
+        .add(test_support::config_account(&fixture.program_id, Pubkey::new_unique(), &config));
+    let deactivate = GhostInstruction::SetPoolActive { active: false };
+    fixture
+        .bank
+        .process(&fixture.program_id, &[config, admin, fixture.pool], &deactivate)
+        .unwrap();
+    let drain_keys = [config, admin, fixture.pool, destination];
+    let drain = GhostInstruction::DrainPool { destination };
+    assert_eq!(
+        fixture.bank.process(&fixture.program_id, &drain_keys, &drain),
+        Err(GhostError::InvalidState.into())
+    );
+
+    // Withdrawing every LP share leaves only the locked ones
+    let shares = fixture.bank.get(&fixture.position).state::<LPPosition>().shares;
+    fixture.withdraw(shares).unwrap();
+    fixture.bank.process(&fixture.program_id, &drain_keys, &drain).unwrap();
+
+    let pool = fixture.bank.get(&fixture.pool).pool();
+    assert!(pool.closed);
+    assert_eq!((pool.total_shares, pool.total_deposited, pool.available_liquidity), (0, 0, 0));
+    assert_eq!(fixture.bank.get(&destination).lamports, MINIMUM_LIQUIDITY);
+    assert_eq!(
+        fixture.bank.get(&fixture.pool).lamports,
+        Rent::default().minimum_balance(LiquidityPool::space())
+    );
+}
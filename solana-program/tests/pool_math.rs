@@ -0,0 +1,132 @@
+use ghost_wallet_solana::test_support;
+use ghost_wallet_solana::{position_value, GhostError, LPPosition, LiquidityPool};
+use solana_program::pubkey::Pubkey;
+
+fn pool(total_deposited: u64, total_shares: u64) -> LiquidityPool {
+    LiquidityPool {
+        total_deposited,
+        total_shares,
+        available_liquidity: total_deposited,
+        ..test_support::pool([0u8; 32])
+    }
+}
+
+fn position(shares: u64) -> LPPosition {
+    LPPosition {
+        owner: Pubkey::new_unique(),
+        pool: [0u8; 32],
+        shares,
+        deposited_at: 0,
+    }
+}
+
+#[test]
+fn position_value_of_zero_shares_is_zero() {
+    assert_eq!(position_value(&pool(1_000, 1_000), &position(0)), 0);
+    // An empty pool values every position at nothing rather than dividing by zero
+    assert_eq!(position_value(&pool(0, 0), &position(0)), 0);
+    assert_eq!(position_value(&pool(0, 0), &position(500)), 0);
+}
+
+#[test]
+fn position_value_rounds_down() {
+    // A 1_000 lamport pool split into 3 shares is worth 333.33.. a share
+    assert_eq!(position_value(&pool(1_000, 3), &position(1)), 333);
+    assert_eq!(position_value(&pool(1_000, 3), &position(2)), 666);
+    assert_eq!(position_value(&pool(1_000, 3), &position(3)), 1_000);
+    // Fees have raised the share price above 1:1
+    assert_eq!(position_value(&pool(1_500, 1_000), &position(1)), 1);
+    assert_eq!(position_value(&pool(1_500, 1_000), &position(3)), 4);
+}
+
+#[test]
+fn position_value_matches_withdrawal_math_without_overflow() {
+    let pool = pool(u64::MAX, u64::MAX / 2);
+    assert_eq!(position_value(&pool, &position(u64::MAX / 2)), u64::MAX);
+    assert_eq!(position_value(&pool, &position(7)), pool.shares_value(7));
+}
+
+/// Share price as a fraction, compared by cross-multiplying
+fn price_not_below(pool: &LiquidityPool, deposited: u64, shares: u64) -> bool {
+    pool.total_deposited as u128 * shares as u128 >= deposited as u128 * pool.total_shares as u128
+}
+
+#[test]
+fn rounding_dust_never_drains_the_pool() {
+    // Awkward amounts and a fee-inflated price so every step rounds
+    let mut pool = pool(1_000_003, 999_983);
+    let mut holdings: Vec<u64> = vec![999_983];
+    let mut seed = 0x2545_f491_u64;
+    let mut next = |bound: u64| {
+        seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        1 + (seed >> 33) % bound
+    };
+
+    for round in 0..2_000 {
+        let (deposited, shares) = (pool.total_deposited, pool.total_shares);
+        if round % 3 != 2 || holdings.is_empty() {
+            let amount = next(50_000);
+            let Some(minted) = pool.shares_for_deposit(amount) else {
+                continue;
+            };
+            pool.total_deposited += amount;
+            pool.total_shares += minted;
+            holdings.push(minted);
+        } else {
+            let index = (next(holdings.len() as u64) - 1) as usize;
+            let burned = next(holdings[index]);
+            let paid = pool.shares_value(burned);
+            pool.total_deposited -= paid;
+            pool.total_shares -= burned;
+            holdings[index] -= burned;
+        }
+        assert!(price_not_below(&pool, deposited, shares), "share price fell in round {round}");
+    }
+
+    // Every LP exits in turn; nobody is paid more than their implied value,
+    // so the dust each exit leaves behind only raises the price for the rest
+    for held in holdings {
+        let (deposited, shares) = (pool.total_deposited, pool.total_shares);
+        let paid = pool.shares_value(held);
+        assert!(paid as u128 * shares as u128 <= held as u128 * deposited as u128);
+        pool.total_deposited -= paid;
+        pool.total_shares -= held;
+        assert!(pool.total_shares == 0 || price_not_below(&pool, deposited, shares));
+    }
+    // Only rounding dust is left behind, never a shortfall
+    assert_eq!(pool.total_shares, 0);
+    assert!(pool.total_deposited < 2_000, "{} lamports of dust", pool.total_deposited);
+}
+
+fn breaker(window_secs: i64, max_volume: u64) -> LiquidityPool {
+    LiquidityPool {
+        breaker_window_secs: window_secs,
+        breaker_max_volume: max_volume,
+        ..pool(0, 0)
+    }
+}
+
+#[test]
+fn breaker_refuses_payouts_past_the_window_limit() {
+    let mut pool = breaker(60, 1_000);
+    pool.record_breaker_payout(100, 600).unwrap();
+    assert_eq!(pool.record_breaker_payout(110, 401), Err(GhostError::BreakerTripped));
+    assert_eq!(pool.breaker_volume, 600);
+    assert!(!pool.breaker_tripped);
+
+    // Smaller payouts still fit; using up the window trips the breaker
+    pool.record_breaker_payout(120, 400).unwrap();
+    assert!(pool.breaker_tripped);
+    assert_eq!(pool.record_breaker_payout(200, 1), Err(GhostError::BreakerTripped));
+}
+
+#[test]
+fn breaker_window_is_fixed() {
+    let mut pool = breaker(60, 1_000);
+    pool.record_breaker_payout(100, 900).unwrap();
+    // A new window starts 60 seconds after the first, not after the last payout
+    assert_eq!(pool.record_breaker_payout(159, 101), Err(GhostError::BreakerTripped));
+    pool.record_breaker_payout(160, 900).unwrap();
+    assert_eq!(pool.breaker_window_start, 160);
+    assert_eq!(pool.breaker_volume, 900);
+}